@@ -0,0 +1,58 @@
+use crate::config::GameConfig;
+use crate::game::{GameState, GuessingGame};
+use crate::secret::SeededSecret;
+use crate::solver::{solve, Solver};
+use std::collections::BTreeMap;
+
+// 一个策略跑完多局游戏后的统计
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub strategy: &'static str,
+    pub games: u32,
+    pub wins: u32,
+    pub total_attempts: u64,
+    pub max_attempts: u32,
+    // 猜测次数 -> 局数
+    pub histogram: BTreeMap<u32, u32>,
+}
+
+impl BenchReport {
+    pub fn average(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.total_attempts as f64 / f64::from(self.games)
+    }
+}
+
+// 二分查找在n个数中最多需要的次数，即ceil(log2(n + 1))
+// n是2的幂时比ceil(log2(n))多一次，比如1..=1024需要11次
+pub fn optimal_bound(config: &GameConfig) -> u32 {
+    let n = u64::from(config.high - config.low) + 1;
+    64 - n.leading_zeros()
+}
+
+// 用同一个种子生成每局的秘密数字，不同策略面对的是完全相同的题目
+pub fn run<S: Solver + ?Sized>(config: GameConfig, seed: u64, games: u32, solver: &mut S) -> BenchReport {
+    let mut secrets = SeededSecret::new(seed);
+    let mut report = BenchReport {
+        strategy: solver.name(),
+        games,
+        wins: 0,
+        total_attempts: 0,
+        max_attempts: 0,
+        histogram: BTreeMap::new(),
+    };
+
+    for _ in 0..games {
+        let mut game = GuessingGame::from_source(config, &mut secrets);
+        if solve(&mut game, solver, |_, _| {}) == GameState::Won {
+            report.wins += 1;
+        }
+        let attempts = game.attempts();
+        report.total_attempts += u64::from(attempts);
+        report.max_attempts = report.max_attempts.max(attempts);
+        *report.histogram.entry(attempts).or_insert(0) += 1;
+    }
+    report
+}
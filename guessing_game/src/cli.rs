@@ -0,0 +1,559 @@
+use guessing_game::{
+    mastermind, solver, ConfigError, DomainKind, GameConfig, Lang, Rules, ScoreFilter, ServerConfig,
+};
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// 子命令，不写子命令时默认是play
+#[derive(Debug)]
+pub enum Command {
+    Play(PlayOptions),
+    Scores(ScoresOptions),
+    Bench(BenchOptions),
+    Reverse(ReverseOptions),
+    Serve(ServeOptions),
+    Replay(ReplayOptions),
+    Domain(DomainOptions),
+    Mastermind(MastermindOptions),
+}
+
+// play子命令的参数
+#[derive(Debug, Default)]
+pub struct PlayOptions {
+    // 指定种子后秘密数字可以复现
+    pub seed: Option<u64>,
+    pub config: GameConfig,
+    pub player: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    // 指定策略时由电脑自己玩
+    pub solver: Option<String>,
+    // 把这局游戏的记录保存到文件
+    pub record: Option<PathBuf>,
+    // 使用全屏的终端界面，默认是按行输入输出方便脚本调用
+    pub tui: bool,
+    // 没有指定时根据LANG等环境变量选择
+    pub lang: Option<Lang>,
+    // 限时模式，必须在这么长时间内猜中
+    pub time_limit: Option<Duration>,
+    // 每日挑战，秘密数字由当天的日期决定
+    pub daily: bool,
+}
+
+// scores子命令的参数
+#[derive(Debug)]
+pub struct ScoresOptions {
+    pub top: usize,
+    pub filter: ScoreFilter,
+    pub reset: bool,
+    pub scores_file: Option<PathBuf>,
+    pub lang: Option<Lang>,
+}
+
+// bench子命令的参数
+#[derive(Debug)]
+pub struct BenchOptions {
+    pub games: u32,
+    pub seed: u64,
+    pub config: GameConfig,
+    // 为空时跑所有内置策略
+    pub strategies: Vec<String>,
+    pub lang: Option<Lang>,
+}
+
+// reverse子命令的参数，玩家想数字，电脑来猜
+#[derive(Debug)]
+pub struct ReverseOptions {
+    pub config: GameConfig,
+    pub lang: Option<Lang>,
+    pub solver: String,
+    pub seed: u64,
+}
+
+// serve子命令的参数，启动多人联机的服务
+#[derive(Debug)]
+pub struct ServeOptions {
+    pub addr: String,
+    pub seed: Option<u64>,
+    pub config: ServerConfig,
+    pub lang: Option<Lang>,
+}
+
+// replay子命令的参数，重放录下的一局并检查反馈
+#[derive(Debug)]
+pub struct ReplayOptions {
+    pub file: PathBuf,
+    pub lang: Option<Lang>,
+}
+
+// domain子命令的参数，猜字母、小数或者单词
+// 范围按字符串保存，等知道是哪种Domain之后再解析
+#[derive(Debug)]
+pub struct DomainOptions {
+    pub kind: DomainKind,
+    pub low: Option<String>,
+    pub high: Option<String>,
+    // 小数的位数和允许的误差
+    pub precision: u32,
+    pub tolerance: Option<String>,
+    // 单词的词典文件，每行一个词
+    pub words: Option<PathBuf>,
+    pub max_attempts: Option<u32>,
+    pub seed: Option<u64>,
+    pub lang: Option<Lang>,
+}
+
+// mastermind子命令的参数
+#[derive(Debug)]
+pub struct MastermindOptions {
+    pub rules: Rules,
+    pub seed: Option<u64>,
+    pub lang: Option<Lang>,
+    // 由Knuth解法自己玩
+    pub solver: bool,
+    // 对每一个可能的答案跑一遍Knuth解法，检查最多要猜几次
+    pub verify: bool,
+}
+
+// 命令行参数错误，直接展示给用户
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<ConfigError> for UsageError {
+    fn from(e: ConfigError) -> UsageError {
+        UsageError(e.to_string())
+    }
+}
+
+pub const USAGE: &str = "usage:
+  guessing_game [play] [--seed <u64>] [--config <file>] [--difficulty easy|normal|hard|insane]
+                [--low <u32>] [--high <u32>] [--max-attempts <u32>] [--name <player>] [--scores-file <file>]
+                [--solver binary|random|linear] [--record <file>] [--tui] [--lang en|zh-CN]
+                [--time-limit <secs>] [--daily]
+  guessing_game replay <file> [--lang en|zh-CN]
+  guessing_game domain numbers|letters|decimals|words [--low <value>] [--high <value>] [--precision <n>]
+                [--tolerance <value>] [--words <file>] [--max-attempts <u32>] [--seed <u64>] [--lang en|zh-CN]
+  guessing_game mastermind [--pegs <n>] [--colours <n>] [--max-attempts <u32>] [--seed <u64>]
+                [--solver knuth] [--verify] [--lang en|zh-CN]    (colours^pegs <= 10000)
+  guessing_game bench [--games <n>] [--seed <u64>] [--strategy <name>]... [--lang en|zh-CN] [<config options>]
+  guessing_game reverse [--solver binary|random|linear] [--seed <u64>] [--lang en|zh-CN] [<config options>]
+  guessing_game serve [--bind <addr>] [--port <u16>] [--idle-timeout <secs>] [--write-timeout <secs>] [--seed <u64>]
+                [--lang en|zh-CN] [<config options>]
+  guessing_game scores [--top <n>] [--difficulty <name>] [--player <name>] [--reset] [--scores-file <file>]
+                [--lang en|zh-CN]";
+
+// 解析不包含程序名的参数列表
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, UsageError> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("play") => {
+            args.next();
+            parse_play(args).map(Command::Play)
+        }
+        Some("scores") => {
+            args.next();
+            parse_scores(args).map(Command::Scores)
+        }
+        Some("bench") => {
+            args.next();
+            parse_bench(args).map(Command::Bench)
+        }
+        Some("reverse") => {
+            args.next();
+            parse_reverse(args).map(Command::Reverse)
+        }
+        Some("replay") => {
+            args.next();
+            parse_replay(args).map(Command::Replay)
+        }
+        Some("serve") => {
+            args.next();
+            parse_serve(args).map(Command::Serve)
+        }
+        Some("domain") => {
+            args.next();
+            parse_domain(args).map(Command::Domain)
+        }
+        Some("mastermind") => {
+            args.next();
+            parse_mastermind(args).map(Command::Mastermind)
+        }
+        _ => parse_play(args).map(Command::Play),
+    }
+}
+
+// 配置相关的参数，play和bench共用
+// 配置的优先级从低到高: 默认配置、难度预设、配置文件、单独的范围和次数参数
+#[derive(Default)]
+struct ConfigArgs {
+    difficulty: Option<String>,
+    file: Option<String>,
+    overrides: Vec<(&'static str, String)>,
+}
+
+impl ConfigArgs {
+    // 如果是配置相关的参数就取出它的值并返回true
+    fn take<I: Iterator<Item = String>>(
+        &mut self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<bool, UsageError> {
+        match arg {
+            "--config" => self.file = Some(take_value(arg, args.next())?),
+            "--difficulty" | "-d" => self.difficulty = Some(take_value(arg, args.next())?),
+            "--low" => self.overrides.push(("low", take_value(arg, args.next())?)),
+            "--high" => self.overrides.push(("high", take_value(arg, args.next())?)),
+            "--max-attempts" => self
+                .overrides
+                .push(("max_attempts", take_value(arg, args.next())?)),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn build(self) -> Result<GameConfig, UsageError> {
+        let mut config = GameConfig::default();
+        if let Some(difficulty) = self.difficulty {
+            config.set("difficulty", &difficulty)?;
+        }
+        if let Some(path) = self.file {
+            config.load_file(path)?;
+        }
+        for (key, value) in self.overrides {
+            config.set(key, &value)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+fn parse_play<I: Iterator<Item = String>>(mut args: I) -> Result<PlayOptions, UsageError> {
+    let mut options = PlayOptions::default();
+    let mut config = ConfigArgs::default();
+
+    while let Some(arg) = args.next() {
+        if config.take(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+            "--name" => options.player = Some(take_value(&arg, args.next())?),
+            "--scores-file" => options.scores_file = Some(take_value(&arg, args.next())?.into()),
+            "--solver" => options.solver = Some(solver_name(take_value(&arg, args.next())?)?),
+            "--record" => options.record = Some(take_value(&arg, args.next())?.into()),
+            "--tui" => options.tui = true,
+            "--lang" => options.lang = Some(parse_lang(&arg, args.next())?),
+            "--time-limit" => {
+                let secs: u64 = parse_value(&arg, args.next())?;
+                if secs == 0 {
+                    return Err(UsageError(String::from(
+                        "--time-limit must be at least 1 second",
+                    )));
+                }
+                options.time_limit = Some(Duration::from_secs(secs));
+            }
+            "--daily" => options.daily = true,
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+    // 回放时没有计时器，也没法重新算出每日挑战的数字
+    if (options.time_limit.is_some() || options.daily) && options.record.is_some() {
+        return Err(UsageError(String::from(
+            "--record cannot be combined with --time-limit or --daily",
+        )));
+    }
+    if options.daily && options.seed.is_some() {
+        return Err(UsageError(String::from(
+            "--daily cannot be combined with --seed",
+        )));
+    }
+    if options.time_limit.is_some() && options.solver.is_some() {
+        return Err(UsageError(String::from(
+            "--time-limit cannot be combined with --solver",
+        )));
+    }
+    if options.tui && (options.record.is_some() || options.solver.is_some()) {
+        return Err(UsageError(String::from(
+            "--tui cannot be combined with --record or --solver",
+        )));
+    }
+    if options.tui && cfg!(not(feature = "tui")) {
+        return Err(UsageError(String::from(
+            "this build has no terminal UI, rebuild with the tui feature",
+        )));
+    }
+
+    options.config = config.build()?;
+    Ok(options)
+}
+
+fn parse_bench<I: Iterator<Item = String>>(mut args: I) -> Result<BenchOptions, UsageError> {
+    let mut games = 1000;
+    let mut seed = 0;
+    let mut strategies = Vec::new();
+    let mut lang = None;
+    let mut config = ConfigArgs::default();
+
+    while let Some(arg) = args.next() {
+        if config.take(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--games" | "-n" => games = parse_value(&arg, args.next())?,
+            "--seed" => seed = parse_value(&arg, args.next())?,
+            "--strategy" | "-s" => strategies.push(solver_name(take_value(&arg, args.next())?)?),
+            "--lang" => lang = Some(parse_lang(&arg, args.next())?),
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+
+    Ok(BenchOptions {
+        games,
+        seed,
+        config: config.build()?,
+        strategies,
+        lang,
+    })
+}
+
+fn parse_reverse<I: Iterator<Item = String>>(mut args: I) -> Result<ReverseOptions, UsageError> {
+    let mut solver = String::from("binary");
+    let mut seed = 0;
+    let mut lang = None;
+    let mut config = ConfigArgs::default();
+
+    while let Some(arg) = args.next() {
+        if config.take(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--solver" => solver = solver_name(take_value(&arg, args.next())?)?,
+            "--seed" => seed = parse_value(&arg, args.next())?,
+            "--lang" => lang = Some(parse_lang(&arg, args.next())?),
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+
+    Ok(ReverseOptions {
+        config: config.build()?,
+        lang,
+        solver,
+        seed,
+    })
+}
+
+fn parse_serve<I: Iterator<Item = String>>(mut args: I) -> Result<ServeOptions, UsageError> {
+    let mut host = String::from("127.0.0.1");
+    let mut port: u16 = 7878;
+    let mut seed = None;
+    let mut lang = None;
+    let mut server = ServerConfig::default();
+    let mut config = ConfigArgs::default();
+
+    while let Some(arg) = args.next() {
+        if config.take(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--bind" => host = take_value(&arg, args.next())?,
+            "--port" | "-p" => port = parse_value(&arg, args.next())?,
+            "--idle-timeout" => {
+                server.idle_timeout = Duration::from_secs(parse_value(&arg, args.next())?)
+            }
+            "--write-timeout" => {
+                server.write_timeout = Duration::from_secs(parse_value(&arg, args.next())?)
+            }
+            "--seed" => seed = Some(parse_value(&arg, args.next())?),
+            "--lang" => lang = Some(parse_lang(&arg, args.next())?),
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+
+    server.game = config.build()?;
+    Ok(ServeOptions {
+        addr: format!("{}:{}", host, port),
+        seed,
+        config: server,
+        lang,
+    })
+}
+
+fn parse_replay<I: Iterator<Item = String>>(mut args: I) -> Result<ReplayOptions, UsageError> {
+    let mut file = None;
+    let mut lang = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lang" => lang = Some(parse_lang(&arg, args.next())?),
+            other if other.starts_with("--") || file.is_some() => {
+                return Err(UsageError(format!("unknown argument: {}", other)))
+            }
+            _ => file = Some(arg),
+        }
+    }
+    Ok(ReplayOptions {
+        file: take_value("replay", file)?.into(),
+        lang,
+    })
+}
+
+fn parse_domain<I: Iterator<Item = String>>(mut args: I) -> Result<DomainOptions, UsageError> {
+    let kind = take_value("domain", args.next())?
+        .parse()
+        .map_err(UsageError)?;
+    let mut options = DomainOptions {
+        kind,
+        low: None,
+        high: None,
+        precision: 2,
+        tolerance: None,
+        words: None,
+        max_attempts: None,
+        seed: None,
+        lang: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--low" => options.low = Some(take_value(&arg, args.next())?),
+            "--high" => options.high = Some(take_value(&arg, args.next())?),
+            "--precision" => options.precision = parse_value(&arg, args.next())?,
+            "--tolerance" => options.tolerance = Some(take_value(&arg, args.next())?),
+            "--words" => options.words = Some(take_value(&arg, args.next())?.into()),
+            "--max-attempts" => options.max_attempts = Some(parse_value(&arg, args.next())?),
+            "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+            "--lang" => options.lang = Some(parse_lang(&arg, args.next())?),
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+    // 精度太高时10的幂会超出i64
+    if options.precision > 9 {
+        return Err(UsageError(String::from("--precision must be at most 9")));
+    }
+    if options.words.is_some() && options.kind != DomainKind::Word {
+        return Err(UsageError(String::from(
+            "--words only applies to the words domain",
+        )));
+    }
+    Ok(options)
+}
+
+fn parse_mastermind<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<MastermindOptions, UsageError> {
+    let mut options = MastermindOptions {
+        rules: Rules::default(),
+        seed: None,
+        lang: None,
+        solver: false,
+        verify: false,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pegs" => options.rules.pegs = parse_value(&arg, args.next())?,
+            "--colours" | "--colors" => options.rules.colours = parse_value(&arg, args.next())?,
+            // 0表示不限次数
+            "--max-attempts" => {
+                let max: u32 = parse_value(&arg, args.next())?;
+                options.rules.max_attempts = Some(max).filter(|&max| max > 0);
+            }
+            "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+            "--lang" => options.lang = Some(parse_lang(&arg, args.next())?),
+            "--solver" => match take_value(&arg, args.next())?.as_str() {
+                "knuth" => options.solver = true,
+                other => {
+                    return Err(UsageError(format!(
+                        "unknown strategy '{}', expected knuth",
+                        other
+                    )))
+                }
+            },
+            "--verify" => options.verify = true,
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+    if options.rules.pegs == 0 {
+        return Err(UsageError(String::from("--pegs must be at least 1")));
+    }
+    if !(2..=mastermind::MAX_COLOURS).contains(&options.rules.colours) {
+        return Err(UsageError(format!(
+            "--colours must be between 2 and {}",
+            mastermind::MAX_COLOURS
+        )));
+    }
+    // 限制的是组合的总数，Knuth解法要枚举所有组合，单独限制位数和颜色数挡不住6位9色这样的组合
+    let codes = u32::try_from(options.rules.pegs)
+        .ok()
+        .and_then(|pegs| usize::from(options.rules.colours).checked_pow(pegs))
+        .filter(|&codes| codes <= mastermind::MAX_CODES);
+    if codes.is_none() {
+        return Err(UsageError(format!(
+            "--colours to the power of --pegs must be at most {} ({} colours and {} pegs is too many codes)",
+            mastermind::MAX_CODES,
+            options.rules.colours,
+            options.rules.pegs
+        )));
+    }
+    Ok(options)
+}
+
+fn solver_name(name: String) -> Result<String, UsageError> {
+    if solver::SOLVERS.contains(&name.as_str()) {
+        Ok(name)
+    } else {
+        Err(UsageError(format!(
+            "unknown strategy '{}', expected one of {}",
+            name,
+            solver::SOLVERS.join("/")
+        )))
+    }
+}
+
+fn parse_scores<I: Iterator<Item = String>>(mut args: I) -> Result<ScoresOptions, UsageError> {
+    let mut options = ScoresOptions {
+        top: 10,
+        filter: ScoreFilter::default(),
+        reset: false,
+        scores_file: None,
+        lang: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--top" | "-n" => options.top = parse_value(&arg, args.next())?,
+            "--difficulty" | "-d" => {
+                options.filter.difficulty = Some(take_value(&arg, args.next())?)
+            }
+            "--player" => options.filter.player = Some(take_value(&arg, args.next())?),
+            "--reset" => options.reset = true,
+            "--scores-file" => options.scores_file = Some(take_value(&arg, args.next())?.into()),
+            "--lang" => options.lang = Some(parse_lang(&arg, args.next())?),
+            other => return Err(UsageError(format!("unknown argument: {}", other))),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_lang(name: &str, value: Option<String>) -> Result<Lang, UsageError> {
+    take_value(name, value)?.parse().map_err(UsageError)
+}
+
+fn take_value(name: &str, value: Option<String>) -> Result<String, UsageError> {
+    value.ok_or_else(|| UsageError(format!("{} requires a value", name)))
+}
+
+// 取出选项后面跟着的值并解析成需要的类型
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, UsageError> {
+    let value = take_value(name, value)?;
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("invalid value for {}: {}", name, value)))
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// 预设的难度，每个难度对应一组范围和猜测次数限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    // hard和insane的次数刚好等于二分查找需要的次数，一步都不能错
+    pub fn config(self) -> GameConfig {
+        let (low, high, max_attempts) = match self {
+            Difficulty::Easy => (1, 20, None),
+            Difficulty::Normal => (1, 100, None),
+            Difficulty::Hard => (1, 1_000, Some(10)),
+            Difficulty::Insane => (1, 1_000_000, Some(20)),
+        };
+        GameConfig {
+            low,
+            high,
+            max_attempts,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Difficulty, ConfigError> {
+        Difficulty::ALL
+            .iter()
+            .copied()
+            .find(|d| d.name() == s)
+            .ok_or_else(|| ConfigError::UnknownDifficulty(s.to_string()))
+    }
+}
+
+// 一局游戏的参数，范围是闭区间[low, high]，max_attempts为None表示不限次数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub low: u32,
+    pub high: u32,
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        Difficulty::Normal.config()
+    }
+}
+
+impl GameConfig {
+    pub fn contains(&self, guess: u32) -> bool {
+        self.low <= guess && guess <= self.high
+    }
+
+    // 和某个预设完全一致时返回对应的难度，否则是自定义的配置
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        Difficulty::ALL.iter().copied().find(|d| d.config() == *self)
+    }
+
+    // 用于展示和分数表分组的难度名称
+    pub fn difficulty_name(&self) -> &'static str {
+        self.difficulty().map_or("custom", Difficulty::name)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.low > self.high {
+            return Err(ConfigError::EmptyRange(self.low, self.high));
+        }
+        if self.max_attempts == Some(0) {
+            return Err(ConfigError::ZeroAttempts);
+        }
+        Ok(())
+    }
+
+    // 读取配置文件并覆盖当前的配置，文件格式是TOML的子集，每行一个 key = value
+    // 支持的key: difficulty、low、high、max_attempts，#开头的是注释
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.display().to_string(), e.to_string()))?;
+        self.apply_str(&content)
+    }
+
+    pub fn apply_str(&mut self, content: &str) -> Result<(), ConfigError> {
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => return Err(ConfigError::Syntax(i + 1, line.to_string())),
+            };
+            self.set(key, value.trim_matches('"'))
+                .map_err(|e| ConfigError::Line(i + 1, Box::new(e)))?;
+        }
+        Ok(())
+    }
+
+    // 设置单个配置项，命令行参数和配置文件共用这个逻辑
+    // difficulty会整体替换范围和次数，所以应该写在其他配置项前面
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue(key.to_string(), value.to_string());
+        match key {
+            "difficulty" => *self = value.parse::<Difficulty>()?.config(),
+            "low" => self.low = value.parse().map_err(|_| invalid())?,
+            "high" => self.high = value.parse().map_err(|_| invalid())?,
+            "max_attempts" => {
+                self.max_attempts = match value {
+                    "none" | "unlimited" => None,
+                    _ => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    UnknownDifficulty(String),
+    UnknownKey(String),
+    InvalidValue(String, String),
+    EmptyRange(u32, u32),
+    ZeroAttempts,
+    Syntax(usize, String),
+    Line(usize, Box<ConfigError>),
+    Io(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::UnknownDifficulty(name) => write!(
+                f,
+                "unknown difficulty '{}', expected one of easy/normal/hard/insane",
+                name
+            ),
+            ConfigError::UnknownKey(key) => write!(f, "unknown config key '{}'", key),
+            ConfigError::InvalidValue(key, value) => {
+                write!(f, "invalid value '{}' for {}", value, key)
+            }
+            ConfigError::EmptyRange(low, high) => {
+                write!(f, "lower bound {} is greater than upper bound {}", low, high)
+            }
+            ConfigError::ZeroAttempts => write!(f, "max attempts must be at least 1"),
+            ConfigError::Syntax(line, text) => {
+                write!(f, "line {}: expected 'key = value', got '{}'", line, text)
+            }
+            ConfigError::Line(line, e) => write!(f, "line {}: {}", line, e),
+            ConfigError::Io(path, e) => write!(f, "cannot read {}: {}", path, e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,646 @@
+// 可以猜的东西不只是整数，只要能随机抽一个、能从输入解析、能比较大小就可以
+// 这里把这三件事抽象成Domain，内置了整数、字母、定点小数和词典里的单词
+use crate::config::GameConfig;
+use crate::frontend::Frontend;
+use crate::game::{Feedback, GameState};
+use crate::i18n::{Lang, Msg};
+use crate::input::{parse_guess, InputError};
+use crate::secret::SecretSource;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// 一种可以猜的值的集合
+pub trait Domain {
+    type Value: Ord + Clone + fmt::Display;
+
+    fn kind(&self) -> DomainKind;
+
+    // 最小值和最大值，用于提示玩家范围
+    fn bounds(&self) -> (Self::Value, Self::Value);
+
+    // 用秘密数字的来源抽一个值，这样种子和每日挑战对所有Domain都有效
+    fn sample(&self, source: &mut dyn SecretSource) -> Self::Value;
+
+    fn parse(&self, input: &str) -> Result<Self::Value, DomainError>;
+
+    // 猜测和答案比较，默认就是值本身的顺序，小数可以在误差内算猜中
+    fn compare(&self, guess: &Self::Value, secret: &Self::Value) -> Ordering {
+        guess.cmp(secret)
+    }
+
+    // 允许的误差，只有小数有，开局时告诉玩家
+    fn tolerance(&self) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainKind {
+    Number,
+    Letter,
+    Decimal,
+    Word,
+}
+
+impl DomainKind {
+    pub const ALL: [DomainKind; 4] = [
+        DomainKind::Number,
+        DomainKind::Letter,
+        DomainKind::Decimal,
+        DomainKind::Word,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DomainKind::Number => "numbers",
+            DomainKind::Letter => "letters",
+            DomainKind::Decimal => "decimals",
+            DomainKind::Word => "words",
+        }
+    }
+}
+
+impl FromStr for DomainKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DomainKind, String> {
+        DomainKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = DomainKind::ALL.iter().map(|k| k.name()).collect();
+                format!(
+                    "unknown domain '{}', expected one of {}",
+                    s,
+                    names.join("/")
+                )
+            })
+    }
+}
+
+// 输入不能作为猜测，或者Domain本身的参数不合法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainError {
+    Empty,
+    // 整数沿用原来的输入错误
+    Number(InputError),
+    NotALetter(String),
+    NotADecimal(String),
+    // 小数位数超过了精度
+    TooPrecise {
+        input: String,
+        precision: u32,
+    },
+    UnknownWord(String),
+    // 珠玑妙算的猜测只能包含数字，并且长度要和答案一样
+    NotACode(String),
+    WrongLength {
+        input: String,
+        expected: usize,
+    },
+    OutOfRange {
+        value: String,
+        low: String,
+        high: String,
+    },
+    // 构造Domain时最小值大于最大值，或者词典是空的
+    EmptyRange,
+    // 可选的值太多，没法用u32的秘密数字抽样
+    RangeTooLarge,
+    // 小数的最小值、最大值和误差的小数位数不一样
+    MixedPrecision {
+        low: u32,
+        high: u32,
+        tolerance: u32,
+    },
+    Eof,
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Lang::En.text(Msg::Domain(self)))
+    }
+}
+
+impl std::error::Error for DomainError {}
+
+// 原来的整数游戏也是一种Domain
+impl Domain for GameConfig {
+    type Value = u32;
+
+    fn kind(&self) -> DomainKind {
+        DomainKind::Number
+    }
+
+    fn bounds(&self) -> (u32, u32) {
+        (self.low, self.high)
+    }
+
+    fn sample(&self, source: &mut dyn SecretSource) -> u32 {
+        source.next_secret(self.low, self.high)
+    }
+
+    fn parse(&self, input: &str) -> Result<u32, DomainError> {
+        parse_guess(input, self).map_err(|e| match e {
+            InputError::Empty => DomainError::Empty,
+            e => DomainError::Number(e),
+        })
+    }
+}
+
+// 单个小写字母，按字母表顺序比较
+#[derive(Debug, Clone, Copy)]
+pub struct Letters {
+    low: char,
+    high: char,
+}
+
+impl Letters {
+    pub fn new(low: char, high: char) -> Result<Letters, DomainError> {
+        let (low, high) = (low.to_ascii_lowercase(), high.to_ascii_lowercase());
+        for c in [low, high] {
+            if !c.is_ascii_lowercase() {
+                return Err(DomainError::NotALetter(c.to_string()));
+            }
+        }
+        if low > high {
+            return Err(DomainError::EmptyRange);
+        }
+        Ok(Letters { low, high })
+    }
+}
+
+impl Default for Letters {
+    fn default() -> Letters {
+        Letters {
+            low: 'a',
+            high: 'z',
+        }
+    }
+}
+
+impl Domain for Letters {
+    type Value = char;
+
+    fn kind(&self) -> DomainKind {
+        DomainKind::Letter
+    }
+
+    fn bounds(&self) -> (char, char) {
+        (self.low, self.high)
+    }
+
+    fn sample(&self, source: &mut dyn SecretSource) -> char {
+        let offset = source.next_secret(0, u32::from(self.high) - u32::from(self.low));
+        char::from_u32(u32::from(self.low) + offset).unwrap_or(self.low)
+    }
+
+    fn parse(&self, input: &str) -> Result<char, DomainError> {
+        let input = input.trim();
+        let mut chars = input.chars();
+        let c = match (chars.next(), chars.next()) {
+            (None, _) => return Err(DomainError::Empty),
+            (Some(c), None) if c.is_ascii_alphabetic() => c.to_ascii_lowercase(),
+            _ => return Err(DomainError::NotALetter(input.to_string())),
+        };
+        if c < self.low || c > self.high {
+            return Err(DomainError::OutOfRange {
+                value: c.to_string(),
+                low: self.low.to_string(),
+                high: self.high.to_string(),
+            });
+        }
+        Ok(c)
+    }
+}
+
+// 定点小数，units是乘以10的precision次方之后的整数，避免浮点数比较的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Decimal {
+    units: i64,
+    precision: u32,
+}
+
+impl Decimal {
+    // 按固定的小数位数解析，位数不够时补零，超过时报错而不是悄悄舍入
+    pub fn parse(input: &str, precision: u32) -> Result<Decimal, DomainError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(DomainError::Empty);
+        }
+        let not_a_decimal = || DomainError::NotADecimal(input.to_string());
+
+        let (negative, digits) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input.strip_prefix('+').unwrap_or(input)),
+        };
+        let (int_part, frac_part) = match digits.split_once('.') {
+            Some((int_part, frac_part)) => (int_part, frac_part),
+            None => (digits, ""),
+        };
+        let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !all_digits(int_part)
+            || !all_digits(frac_part)
+        {
+            return Err(not_a_decimal());
+        }
+        if frac_part.len() > precision as usize {
+            return Err(DomainError::TooPrecise {
+                input: input.to_string(),
+                precision,
+            });
+        }
+
+        let scale = 10i64.checked_pow(precision).ok_or_else(not_a_decimal)?;
+        let int_value: i64 = if int_part.is_empty() {
+            0
+        } else {
+            int_part.parse().map_err(|_| not_a_decimal())?
+        };
+        let frac_value: i64 = format!("{:0<width$}", frac_part, width = precision as usize)
+            .parse()
+            .unwrap_or(0);
+        let units = int_value
+            .checked_mul(scale)
+            .and_then(|v| v.checked_add(frac_value))
+            .ok_or_else(not_a_decimal)?;
+        Ok(Decimal {
+            units: if negative { -units } else { units },
+            precision,
+        })
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = 10u64.pow(self.precision);
+        let sign = if self.units < 0 { "-" } else { "" };
+        let abs = self.units.unsigned_abs();
+        if self.precision == 0 {
+            write!(f, "{}{}", sign, abs)
+        } else {
+            write!(
+                f,
+                "{}{}.{:0width$}",
+                sign,
+                abs / scale,
+                abs % scale,
+                width = self.precision as usize
+            )
+        }
+    }
+}
+
+// 一段定点小数，和答案相差不超过tolerance就算猜中
+#[derive(Debug, Clone, Copy)]
+pub struct Decimals {
+    low: Decimal,
+    high: Decimal,
+    tolerance: Decimal,
+}
+
+impl Decimals {
+    // 三个值的精度必须相同，用Decimal::parse按同一个精度解析即可
+    pub fn new(low: Decimal, high: Decimal, tolerance: Decimal) -> Result<Decimals, DomainError> {
+        if low.precision != high.precision || low.precision != tolerance.precision {
+            return Err(DomainError::MixedPrecision {
+                low: low.precision,
+                high: high.precision,
+                tolerance: tolerance.precision,
+            });
+        }
+        if low > high || tolerance.units < 0 {
+            return Err(DomainError::EmptyRange);
+        }
+        match high.units.checked_sub(low.units) {
+            Some(span) if span as u64 <= u64::from(u32::MAX) => {}
+            _ => return Err(DomainError::RangeTooLarge),
+        }
+        Ok(Decimals {
+            low,
+            high,
+            tolerance,
+        })
+    }
+}
+
+impl Domain for Decimals {
+    type Value = Decimal;
+
+    fn kind(&self) -> DomainKind {
+        DomainKind::Decimal
+    }
+
+    fn bounds(&self) -> (Decimal, Decimal) {
+        (self.low, self.high)
+    }
+
+    fn sample(&self, source: &mut dyn SecretSource) -> Decimal {
+        let offset = source.next_secret(0, (self.high.units - self.low.units) as u32);
+        Decimal {
+            units: self.low.units + i64::from(offset),
+            precision: self.low.precision,
+        }
+    }
+
+    fn parse(&self, input: &str) -> Result<Decimal, DomainError> {
+        let value = Decimal::parse(input, self.low.precision)?;
+        if value < self.low || value > self.high {
+            return Err(DomainError::OutOfRange {
+                value: value.to_string(),
+                low: self.low.to_string(),
+                high: self.high.to_string(),
+            });
+        }
+        Ok(value)
+    }
+
+    fn compare(&self, guess: &Decimal, secret: &Decimal) -> Ordering {
+        if (guess.units - secret.units).abs() <= self.tolerance.units {
+            Ordering::Equal
+        } else {
+            guess.cmp(secret)
+        }
+    }
+
+    fn tolerance(&self) -> Option<String> {
+        Some(self.tolerance.to_string())
+    }
+}
+
+// 没有指定词典文件时使用的单词
+const BUILTIN_WORDS: &[&str] = &[
+    "apple", "banana", "borrow", "bridge", "candle", "castle", "cherry", "cloud", "copper",
+    "crate", "dragon", "eagle", "engine", "falcon", "forest", "garden", "ginger", "guitar",
+    "harbor", "honey", "island", "jungle", "kettle", "ladder", "lemon", "lifetime", "marble",
+    "meadow", "mirror", "needle", "orange", "oyster", "pepper", "planet", "pocket", "puzzle",
+    "rabbit", "river", "rocket", "saddle", "silver", "slice", "spider", "string", "thread",
+    "tiger", "trait", "tunnel", "velvet", "violin", "walnut", "window", "winter", "yellow",
+    "zebra",
+];
+
+// 词典里的单词，按字典序比较，只接受词典里有的词
+#[derive(Debug, Clone)]
+pub struct Words {
+    // 排好序并且去重
+    words: Vec<String>,
+}
+
+impl Words {
+    pub fn new<I: IntoIterator<Item = String>>(words: I) -> Result<Words, DomainError> {
+        let mut words: Vec<String> = words
+            .into_iter()
+            .map(|w| w.trim().to_lowercase())
+            .filter(|w| !w.is_empty() && w.chars().all(char::is_alphabetic))
+            .collect();
+        words.sort();
+        words.dedup();
+        if words.is_empty() {
+            return Err(DomainError::EmptyRange);
+        }
+        if words.len() as u64 > u64::from(u32::MAX) {
+            return Err(DomainError::RangeTooLarge);
+        }
+        Ok(Words { words })
+    }
+
+    // 每行一个单词，比如 /usr/share/dict/words，包含非字母的行会被忽略
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Words> {
+        let text = fs::read_to_string(path)?;
+        Words::new(text.lines().map(String::from))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+impl Default for Words {
+    fn default() -> Words {
+        Words {
+            words: BUILTIN_WORDS.iter().map(|w| w.to_string()).collect(),
+        }
+    }
+}
+
+impl Domain for Words {
+    type Value = String;
+
+    fn kind(&self) -> DomainKind {
+        DomainKind::Word
+    }
+
+    fn bounds(&self) -> (String, String) {
+        (
+            self.words[0].clone(),
+            self.words[self.words.len() - 1].clone(),
+        )
+    }
+
+    fn sample(&self, source: &mut dyn SecretSource) -> String {
+        let index = source.next_secret(0, self.words.len() as u32 - 1);
+        self.words[index as usize].clone()
+    }
+
+    fn parse(&self, input: &str) -> Result<String, DomainError> {
+        let word = input.trim().to_lowercase();
+        if word.is_empty() {
+            return Err(DomainError::Empty);
+        }
+        match self.words.binary_search(&word) {
+            Ok(_) => Ok(word),
+            Err(_) => Err(DomainError::UnknownWord(word)),
+        }
+    }
+}
+
+// 和GuessingGame一样的规则，只是值的类型由Domain决定
+// 没有范围收窄和提示，这两样都依赖整数的性质
+pub struct DomainGame<D: Domain> {
+    domain: D,
+    secret: D::Value,
+    max_attempts: Option<u32>,
+    attempts: u32,
+    state: GameState,
+    history: Vec<D::Value>,
+}
+
+impl<D: Domain> DomainGame<D> {
+    pub fn new(domain: D, secret: D::Value, max_attempts: Option<u32>) -> DomainGame<D> {
+        DomainGame {
+            domain,
+            secret,
+            max_attempts,
+            attempts: 0,
+            state: GameState::Playing,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn from_source(
+        domain: D,
+        max_attempts: Option<u32>,
+        source: &mut dyn SecretSource,
+    ) -> DomainGame<D> {
+        let secret = domain.sample(source);
+        DomainGame::new(domain, secret, max_attempts)
+    }
+
+    pub fn domain(&self) -> &D {
+        &self.domain
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn history(&self) -> &[D::Value] {
+        &self.history
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.max_attempts
+    }
+
+    // 和GuessingGame一样，游戏结束之前不透露答案
+    pub fn secret(&self) -> Option<&D::Value> {
+        match self.state {
+            GameState::Playing => None,
+            _ => Some(&self.secret),
+        }
+    }
+
+    // 猜测必须是parse得到的值，所以这里不用再检查范围
+    pub fn guess(&mut self, value: D::Value) -> Feedback {
+        if self.state != GameState::Playing {
+            return Feedback::GameOver;
+        }
+        self.attempts += 1;
+        let ordering = self.domain.compare(&value, &self.secret);
+        self.history.push(value);
+
+        let feedback = match ordering {
+            Ordering::Less => Feedback::TooSmall,
+            Ordering::Greater => Feedback::TooBig,
+            Ordering::Equal => Feedback::Correct,
+        };
+        if feedback == Feedback::Correct {
+            self.state = GameState::Won;
+        } else if self.max_attempts.is_some_and(|max| self.attempts >= max) {
+            self.state = GameState::Lost;
+        }
+        feedback
+    }
+
+    pub fn give_up(&mut self) {
+        if self.state == GameState::Playing {
+            self.state = GameState::Lost;
+        }
+    }
+}
+
+// 读取一次输入的结果
+pub enum Turn<T> {
+    Guess(T),
+    // 输入不合法，已经提示过玩家，再读一次
+    Retry,
+    // 输入结束或者时间到了，这一局结束
+    End,
+}
+
+// 回合循环里共用的一步：提示、读一行、解析，出错时把错误展示给玩家
+pub fn read_guess<T, F, P>(frontend: &mut F, parse: P) -> io::Result<Turn<T>>
+where
+    F: Frontend,
+    P: FnOnce(&str) -> Result<T, DomainError>,
+{
+    frontend.say(Msg::Prompt)?;
+    let parsed = match frontend.read_input() {
+        Ok(Some(input)) => {
+            frontend.say(Msg::YouGuessed(input.trim()))?;
+            parse(&input)
+        }
+        Ok(None) => Err(DomainError::Eof),
+        Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
+            frontend.say(Msg::TimeUp)?;
+            return Ok(Turn::End);
+        }
+        Err(e) => return Err(e),
+    };
+
+    match parsed {
+        Ok(value) => Ok(Turn::Guess(value)),
+        Err(e) => {
+            frontend.say(Msg::Domain(&e))?;
+            Ok(if e == DomainError::Eof {
+                Turn::End
+            } else {
+                Turn::Retry
+            })
+        }
+    }
+}
+
+// 和play一样的回合循环，输入交给Domain解析
+pub fn play_domain<D: Domain, F: Frontend>(
+    game: &mut DomainGame<D>,
+    frontend: &mut F,
+) -> io::Result<GameState> {
+    let (low, high) = game.domain().bounds();
+    frontend.say(Msg::DomainRange {
+        kind: game.domain().kind(),
+        low: &low.to_string(),
+        high: &high.to_string(),
+        max: game.max_attempts(),
+    })?;
+    if let Some(tolerance) = game.domain().tolerance() {
+        frontend.say(Msg::Tolerance(&tolerance))?;
+    }
+
+    while game.state() == GameState::Playing {
+        let kind = game.domain().kind();
+        match read_guess(frontend, |input| game.domain().parse(input))? {
+            Turn::Guess(value) => match game.guess(value) {
+                Feedback::GameOver => break,
+                feedback => frontend.say(Msg::DomainFeedback { kind, feedback })?,
+            },
+            Turn::Retry => {}
+            Turn::End => game.give_up(),
+        }
+    }
+
+    let text = domain_summary(game, frontend.lang());
+    frontend.show(&text)?;
+    Ok(game.state())
+}
+
+pub fn domain_summary<D: Domain>(game: &DomainGame<D>, lang: Lang) -> String {
+    let attempts = game.attempts();
+    match (game.state(), game.secret()) {
+        (GameState::Won, _) => lang.text(Msg::DomainWon { attempts }),
+        (GameState::Lost, Some(secret)) => lang.text(Msg::DomainLost {
+            secret: &secret.to_string(),
+            attempts,
+        }),
+        _ => lang.text(Msg::Unfinished { attempts }),
+    }
+}
@@ -0,0 +1,304 @@
+use crate::game::{Feedback, GameState, GuessingGame};
+use crate::hint::{is_hint_request, parse_hint};
+use crate::i18n::{Lang, Msg};
+use crate::input::{parse_guess, InputError};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 前端只负责读取一行输入和输出一条消息，游戏循环不关心输入输出来自哪里
+pub trait Frontend {
+    // 读取玩家的一行输入，返回None表示输入已经结束
+    fn read_input(&mut self) -> io::Result<Option<String>>;
+
+    fn show(&mut self, msg: &str) -> io::Result<()>;
+
+    // 展示给玩家的语言，默认是英文
+    fn lang(&self) -> Lang {
+        Lang::default()
+    }
+
+    // 按前端的语言翻译消息后展示
+    fn say(&mut self, msg: Msg) -> io::Result<()> {
+        let text = self.lang().text(msg);
+        self.show(&text)
+    }
+}
+
+// 基于行的前端，可以包装标准输入输出、内存缓冲或者网络连接
+pub struct LineFrontend<R, W> {
+    reader: R,
+    writer: W,
+    lang: Lang,
+}
+
+impl<R: BufRead, W: Write> LineFrontend<R, W> {
+    pub fn new(reader: R, writer: W) -> LineFrontend<R, W> {
+        LineFrontend {
+            reader,
+            writer,
+            lang: Lang::default(),
+        }
+    }
+
+    pub fn with_lang(mut self, lang: Lang) -> LineFrontend<R, W> {
+        self.lang = lang;
+        self
+    }
+
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for LineFrontend<R, W> {
+    fn read_input(&mut self) -> io::Result<Option<String>> {
+        // 使用内置的String调用关联函数产生空白的字符串，read_line必须传入可变的引用
+        let mut line = String::new();
+        // 读取到0个字节说明已经到达输入末尾
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    fn show(&mut self, msg: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", msg)?;
+        self.writer.flush()
+    }
+
+    fn lang(&self) -> Lang {
+        self.lang
+    }
+}
+
+// 按脚本依次给出输入并收集所有输出，用于回放和测试
+#[derive(Debug, Default)]
+pub struct ScriptedFrontend {
+    inputs: VecDeque<String>,
+    outputs: Vec<String>,
+    lang: Lang,
+}
+
+impl ScriptedFrontend {
+    pub fn new<I: IntoIterator<Item = String>>(inputs: I) -> ScriptedFrontend {
+        ScriptedFrontend {
+            inputs: inputs.into_iter().collect(),
+            outputs: Vec::new(),
+            lang: Lang::default(),
+        }
+    }
+
+    pub fn with_lang(mut self, lang: Lang) -> ScriptedFrontend {
+        self.lang = lang;
+        self
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+}
+
+impl Frontend for ScriptedFrontend {
+    fn read_input(&mut self) -> io::Result<Option<String>> {
+        Ok(self.inputs.pop_front())
+    }
+
+    fn show(&mut self, msg: &str) -> io::Result<()> {
+        self.outputs.push(msg.to_string());
+        Ok(())
+    }
+
+    fn lang(&self) -> Lang {
+        self.lang
+    }
+}
+
+// 读线程和计时线程发给TimedFrontend的事件
+enum TimedEvent {
+    Line(io::Result<String>),
+    Eof,
+    TimeUp,
+}
+
+// 限时模式的前端，输入在后台线程中读取，另一个后台线程负责倒计时
+// 时间到了之后read_input返回TimedOut错误，即使玩家还没有输入也能立即结束这一轮
+pub struct TimedFrontend<W> {
+    events: Receiver<TimedEvent>,
+    writer: W,
+    deadline: Instant,
+    lang: Lang,
+    expired: bool,
+}
+
+impl<W: Write> TimedFrontend<W> {
+    pub fn new<R: BufRead + Send + 'static>(
+        mut reader: R,
+        writer: W,
+        limit: Duration,
+    ) -> TimedFrontend<W> {
+        let (tx, events) = mpsc::channel();
+
+        let lines = tx.clone();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            let event = match reader.read_line(&mut line) {
+                Ok(0) => TimedEvent::Eof,
+                Ok(_) => TimedEvent::Line(Ok(line)),
+                Err(e) => TimedEvent::Line(Err(e)),
+            };
+            let done = matches!(event, TimedEvent::Eof);
+            // 接收端已经关闭说明游戏结束了，读线程也可以退出
+            if lines.send(event).is_err() || done {
+                break;
+            }
+        });
+
+        thread::spawn(move || {
+            thread::sleep(limit);
+            let _ = tx.send(TimedEvent::TimeUp);
+        });
+
+        TimedFrontend {
+            events,
+            writer,
+            deadline: Instant::now() + limit,
+            lang: Lang::default(),
+            expired: false,
+        }
+    }
+
+    pub fn with_lang(mut self, lang: Lang) -> TimedFrontend<W> {
+        self.lang = lang;
+        self
+    }
+}
+
+impl<W: Write> Frontend for TimedFrontend<W> {
+    fn read_input(&mut self) -> io::Result<Option<String>> {
+        if !self.expired {
+            let left = self.deadline.saturating_duration_since(Instant::now());
+            // 向上取整，刚开始时显示的是完整的限时
+            self.say(Msg::TimeLeft {
+                seconds: (left.as_millis() as u64).div_ceil(1000),
+            })?;
+        }
+        match self.events.recv() {
+            Ok(TimedEvent::Line(line)) => line.map(Some),
+            Ok(TimedEvent::Eof) | Err(_) => Ok(None),
+            Ok(TimedEvent::TimeUp) => {
+                self.expired = true;
+                Err(io::Error::new(io::ErrorKind::TimedOut, "time is up"))
+            }
+        }
+    }
+
+    fn show(&mut self, msg: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", msg)?;
+        self.writer.flush()
+    }
+
+    fn lang(&self) -> Lang {
+        self.lang
+    }
+}
+
+// 通用的游戏循环，直到游戏结束或者输入结束才返回最终状态
+// 输入结束时玩家视为放弃，游戏以Lost结束并输出总结
+pub fn play<F: Frontend>(game: &mut GuessingGame, frontend: &mut F) -> io::Result<GameState> {
+    frontend.say(Msg::Welcome)?;
+    let config = *game.config();
+    match config.max_attempts {
+        Some(max) => frontend.say(Msg::RangeWithAttempts {
+            low: config.low,
+            high: config.high,
+            max,
+        })?,
+        None => frontend.say(Msg::Range {
+            low: config.low,
+            high: config.high,
+        })?,
+    }
+    frontend.say(Msg::HintIntro)?;
+
+    while game.state() == GameState::Playing {
+        frontend.say(Msg::Prompt)?;
+
+        let parsed = match frontend.read_input() {
+            // 以hint开头的输入是请求提示，不算一次猜测
+            Ok(Some(ref input)) if is_hint_request(input) => {
+                show_hint(game, frontend, &input.trim()[4..])?;
+                continue;
+            }
+            Ok(Some(input)) => {
+                frontend.say(Msg::YouGuessed(input.trim()))?;
+                parse_guess(&input, &config)
+            }
+            Ok(None) => Err(InputError::Eof),
+            // 限时模式时间到了，这一轮直接判负
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
+                frontend.say(Msg::TimeUp)?;
+                game.give_up();
+                break;
+            }
+            // 不是合法的UTF-8时这一行已经被读走了，提示玩家后可以继续
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => {
+                Err(InputError::NotANumber(String::from("<invalid UTF-8>")))
+            }
+            Err(e) => return Err(e),
+        };
+
+        let guess = match parsed {
+            Ok(guess) => guess,
+            Err(InputError::Eof) => {
+                frontend.say(Msg::Input(&InputError::Eof))?;
+                game.give_up();
+                break;
+            }
+            Err(e) => {
+                frontend.say(Msg::Input(&e))?;
+                continue;
+            }
+        };
+
+        match game.guess(guess) {
+            Feedback::OutOfRange { low, high } => {
+                let e = InputError::OutOfRange {
+                    value: guess,
+                    low,
+                    high,
+                };
+                frontend.say(Msg::Input(&e))?
+            }
+            Feedback::GameOver => break,
+            feedback => frontend.say(Msg::Feedback(feedback))?,
+        };
+    }
+
+    let text = summary(game, frontend.lang());
+    frontend.show(&text)?;
+    Ok(game.state())
+}
+
+fn show_hint<F: Frontend>(game: &mut GuessingGame, frontend: &mut F, args: &str) -> io::Result<()> {
+    let hint = match parse_hint(args) {
+        Some(hint) => hint,
+        None => return frontend.say(Msg::HintHelp),
+    };
+    match game.hint(hint) {
+        Some(result) => frontend.say(Msg::HintUsed(hint, result)),
+        None => frontend.say(Msg::NoMoreHints),
+    }
+}
+
+// 游戏结束后的总结
+pub fn summary(game: &GuessingGame, lang: Lang) -> String {
+    let attempts = game.attempts();
+    lang.text(match (game.state(), game.secret()) {
+        (GameState::Won, _) => Msg::Won { attempts },
+        (GameState::Lost, Some(secret)) => Msg::Lost { secret, attempts },
+        _ => Msg::Unfinished { attempts },
+    })
+}
@@ -0,0 +1,176 @@
+use crate::config::GameConfig;
+use crate::hint::{Hint, HintResult};
+use crate::secret::{RandomSecret, SecretSource};
+use std::cmp::Ordering;
+
+// 游戏的状态机，只能从Playing转移到Won或者Lost，结束后不会再变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Playing,
+    Won,
+    Lost,
+}
+
+// 每次猜测后给出的反馈，和具体的输出方式无关，由前端决定怎么展示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    TooSmall,
+    TooBig,
+    Correct,
+    // 猜测超出了配置的范围，这次猜测不会被计数
+    OutOfRange { low: u32, high: u32 },
+    // 游戏已经结束，这次猜测不会被计数
+    GameOver,
+}
+
+impl Feedback {
+    // 把比较结果转换成反馈，Ordering是猜测值相对于秘密数字的大小
+    pub fn from_ordering(ordering: Ordering) -> Feedback {
+        match ordering {
+            Ordering::Less => Feedback::TooSmall,
+            Ordering::Greater => Feedback::TooBig,
+            Ordering::Equal => Feedback::Correct,
+        }
+    }
+}
+
+// 一局猜数字游戏，只保存状态不做任何IO
+#[derive(Debug)]
+pub struct GuessingGame {
+    config: GameConfig,
+    secret: u32,
+    attempts: u32,
+    state: GameState,
+    // 计数的猜测，按时间顺序
+    history: Vec<u32>,
+    // 本局用过的提示
+    hints: Vec<Hint>,
+}
+
+impl GuessingGame {
+    // 使用默认配置和指定的秘密数字开始一局游戏
+    pub fn new(secret: u32) -> GuessingGame {
+        GuessingGame::with_config(GameConfig::default(), secret)
+    }
+
+    // 秘密数字应该在配置的范围内，否则这局游戏永远无法获胜
+    pub fn with_config(config: GameConfig, secret: u32) -> GuessingGame {
+        GuessingGame {
+            config,
+            secret,
+            attempts: 0,
+            state: GameState::Playing,
+            history: Vec::new(),
+            hints: Vec::new(),
+        }
+    }
+
+    // 从给定的来源取出配置范围内的秘密数字
+    pub fn from_source<S: SecretSource + ?Sized>(config: GameConfig, source: &mut S) -> GuessingGame {
+        let secret = source.next_secret(config.low, config.high);
+        GuessingGame::with_config(config, secret)
+    }
+
+    // 调用rand方法生产范围内的随机数
+    pub fn random(config: GameConfig) -> GuessingGame {
+        GuessingGame::from_source(config, &mut RandomSecret)
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn history(&self) -> &[u32] {
+        &self.history
+    }
+
+    pub fn hints(&self) -> &[Hint] {
+        &self.hints
+    }
+
+    // 根据目前的反馈，秘密数字一定在这个闭区间内
+    pub fn bounds(&self) -> (u32, u32) {
+        let mut low = self.config.low;
+        let mut high = self.config.high;
+        for &guess in &self.history {
+            match guess.cmp(&self.secret) {
+                Ordering::Less => low = low.max(guess + 1),
+                Ordering::Greater => high = high.min(guess - 1),
+                Ordering::Equal => return (guess, guess),
+            }
+        }
+        (low, high)
+    }
+
+    // 用过的提示一共扣掉的分数
+    pub fn hint_penalty(&self) -> u32 {
+        self.hints.iter().map(|h| h.cost()).sum()
+    }
+
+    // 使用一个提示，游戏结束后返回None，没有给出信息的提示不扣分
+    pub fn hint(&mut self, hint: Hint) -> Option<HintResult> {
+        if self.state != GameState::Playing {
+            return None;
+        }
+        let result = hint.reveal(self.secret, &self.history);
+        if result != HintResult::NeedTwoGuesses {
+            self.hints.push(hint);
+        }
+        Some(result)
+    }
+
+    // 剩余的猜测次数，不限次数时返回None
+    pub fn remaining(&self) -> Option<u32> {
+        self.config
+            .max_attempts
+            .map(|max| max.saturating_sub(self.attempts))
+    }
+
+    // 只有游戏结束后才公开秘密数字，避免前端提前泄露答案
+    pub fn secret(&self) -> Option<u32> {
+        match self.state {
+            GameState::Playing => None,
+            _ => Some(self.secret),
+        }
+    }
+
+    pub fn guess(&mut self, guess: u32) -> Feedback {
+        if self.state != GameState::Playing {
+            return Feedback::GameOver;
+        }
+        if !self.config.contains(guess) {
+            return Feedback::OutOfRange {
+                low: self.config.low,
+                high: self.config.high,
+            };
+        }
+        self.attempts += 1;
+        self.history.push(guess);
+
+        // match 表达式由多个分支组成，每个分支包含一个用于匹配的模式，以及匹配到的执行代码
+        // 匹配代码执行完后就会退出，不再继续执行
+        let feedback = Feedback::from_ordering(guess.cmp(&self.secret));
+        if feedback == Feedback::Correct {
+            self.state = GameState::Won;
+        } else if self.remaining() == Some(0) {
+            // 次数用完还没有猜中就输了，但这次的大小反馈依然返回给玩家
+            self.state = GameState::Lost;
+        }
+        feedback
+    }
+
+    // 玩家主动放弃，游戏进入Lost状态
+    pub fn give_up(&mut self) {
+        if self.state == GameState::Playing {
+            self.state = GameState::Lost;
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::i18n::{Lang, Msg};
+use std::fmt;
+
+// 玩家可以花分数换取的提示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    // 秘密数字是奇数还是偶数
+    Parity,
+    // 秘密数字能否被给定的数整除
+    Divisibility(u32),
+    // 秘密数字各位数字之和
+    DigitSum,
+    // 最近一次猜测比上一次更接近还是更远
+    Temperature,
+}
+
+impl Hint {
+    // 每种提示扣掉的分数，透露的信息越多越贵
+    pub fn cost(self) -> u32 {
+        match self {
+            Hint::Parity => 50,
+            Hint::Divisibility(_) => 75,
+            Hint::DigitSum => 100,
+            Hint::Temperature => 25,
+        }
+    }
+
+    // 回答这个提示，history是本局已经计数的猜测，按时间顺序
+    pub fn reveal(self, secret: u32, history: &[u32]) -> HintResult {
+        match self {
+            Hint::Parity => HintResult::Parity {
+                even: secret.is_multiple_of(2),
+            },
+            Hint::Divisibility(divisor) => HintResult::Divisible {
+                divisor,
+                divisible: secret.is_multiple_of(divisor),
+            },
+            Hint::DigitSum => HintResult::DigitSum(digit_sum(secret)),
+            Hint::Temperature => match history {
+                [.., previous, last] => {
+                    let distance = |guess: u32| (i64::from(guess) - i64::from(secret)).abs();
+                    HintResult::Temperature(distance(*last).cmp(&distance(*previous)))
+                }
+                _ => HintResult::NeedTwoGuesses,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hint::Parity => write!(f, "parity"),
+            Hint::Divisibility(divisor) => write!(f, "div {}", divisor),
+            Hint::DigitSum => write!(f, "digits"),
+            Hint::Temperature => write!(f, "warmer"),
+        }
+    }
+}
+
+// 提示的结果，由前端决定怎么展示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintResult {
+    Parity { even: bool },
+    Divisible { divisor: u32, divisible: bool },
+    DigitSum(u32),
+    // 最近一次猜测的距离和上一次相比，Less表示更近了
+    Temperature(std::cmp::Ordering),
+    // 至少要猜过两次才能比较冷热，这种情况不扣分
+    NeedTwoGuesses,
+}
+
+impl fmt::Display for HintResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Lang::En.text(Msg::HintText(*self)))
+    }
+}
+
+// 以hint开头的一行输入是请求提示，不算一次猜测，行模式和全屏界面都用它判断
+// 后面的参数从第4个字节开始，交给parse_hint解析
+pub fn is_hint_request(input: &str) -> bool {
+    let input = input.trim();
+    input.starts_with("hint") && (input.len() == 4 || input[4..].starts_with(' '))
+}
+
+// 解析 hint 后面的参数，比如 "parity"、"div 3"，没有写除数时默认是3
+pub fn parse_hint(args: &str) -> Option<Hint> {
+    let mut words = args.split_whitespace();
+    let hint = match words.next()? {
+        "parity" | "odd" | "even" => Hint::Parity,
+        "div" | "divisible" => match words.next() {
+            Some(n) => Hint::Divisibility(n.parse().ok().filter(|n| *n > 1)?),
+            None => Hint::Divisibility(3),
+        },
+        "digits" | "digitsum" => Hint::DigitSum,
+        "warmer" | "colder" => Hint::Temperature,
+        _ => return None,
+    };
+    match words.next() {
+        Some(_) => None,
+        None => Some(hint),
+    }
+}
+
+fn digit_sum(mut n: u32) -> u32 {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
@@ -0,0 +1,833 @@
+// 玩家能看到的所有文字都在这里，目前有英文和简体中文两套
+// 每种语言都是对Msg的一个完整match，漏掉任何一条消息都无法通过编译，
+// 所以不会出现某个语言缺少某条消息的情况
+use crate::domain::{DomainError, DomainKind};
+use crate::game::Feedback;
+use crate::hint::{Hint, HintResult};
+use crate::input::InputError;
+use crate::mastermind::Marks;
+use crate::reverse::{Answer, Contradiction};
+use crate::scoring::{Score, ATTEMPT_COST, BASE_POINTS, SECOND_COST};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Lang {
+    // 找不到合适的语言时使用英文
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::En, Lang::ZhCn];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::ZhCn => "zh-CN",
+        }
+    }
+
+    // 解析语言标签，支持 zh-CN、zh_CN.UTF-8 这样的写法，只看主语言
+    pub fn from_tag(tag: &str) -> Option<Lang> {
+        let tag = tag.split('.').next().unwrap_or("").to_lowercase();
+        if tag.starts_with("zh") {
+            Some(Lang::ZhCn)
+        } else if tag.starts_with("en") {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    // 和POSIX一样按 LC_ALL、LC_MESSAGES、LANG 的顺序查找，都没有或者不支持时使用英文
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::from_tag(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn text(self, msg: Msg) -> String {
+        match self {
+            Lang::En => en(msg),
+            Lang::ZhCn => zh_cn(msg),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Lang, String> {
+        Lang::from_tag(s).ok_or_else(|| {
+            let tags: Vec<&str> = Lang::ALL.iter().map(|l| l.tag()).collect();
+            format!(
+                "unsupported language '{}', expected one of {}",
+                s,
+                tags.join("/")
+            )
+        })
+    }
+}
+
+// 所有需要翻译的消息，带参数的消息把参数放在变体里
+#[derive(Debug, Clone, Copy)]
+pub enum Msg<'a> {
+    Welcome,
+    Range {
+        low: u32,
+        high: u32,
+    },
+    RangeWithAttempts {
+        low: u32,
+        high: u32,
+        max: u32,
+    },
+    HintIntro,
+    Prompt,
+    YouGuessed(&'a str),
+    Feedback(Feedback),
+    Input(&'a InputError),
+    HintHelp,
+    HintText(HintResult),
+    HintUsed(Hint, HintResult),
+    NoMoreHints,
+    Won {
+        attempts: u32,
+    },
+    Lost {
+        secret: u32,
+        attempts: u32,
+    },
+    Unfinished {
+        attempts: u32,
+    },
+    SolverGuess {
+        solver: &'a str,
+        guess: u32,
+        feedback: Feedback,
+    },
+    Score(&'a Score),
+    HighScore {
+        rank: usize,
+        difficulty: &'a str,
+    },
+    ReverseIntro {
+        low: u32,
+        high: u32,
+    },
+    ReverseAsk(u32),
+    ReverseBadAnswer,
+    ReverseGotIt {
+        attempts: u32,
+    },
+    ReverseGiveUp,
+    Answer(&'a Answer),
+    Contradiction(&'a Contradiction),
+    TuiHelp,
+    TuiWon,
+    TuiLost {
+        secret: u32,
+    },
+    TuiStats {
+        attempts: u32,
+        remaining: Option<u32>,
+        seconds: u64,
+        limit: Option<u64>,
+        hint_penalty: u32,
+    },
+    TimeLimit {
+        seconds: u64,
+    },
+    TimeLeft {
+        seconds: u64,
+    },
+    TimeUp,
+    Daily {
+        date: &'a str,
+    },
+    TuiHistory,
+    TuiYourGuess,
+    DomainRange {
+        kind: DomainKind,
+        low: &'a str,
+        high: &'a str,
+        max: Option<u32>,
+    },
+    Tolerance(&'a str),
+    DomainFeedback {
+        kind: DomainKind,
+        feedback: Feedback,
+    },
+    Domain(&'a DomainError),
+    DomainWon {
+        attempts: u32,
+    },
+    DomainLost {
+        secret: &'a str,
+        attempts: u32,
+    },
+    MastermindIntro {
+        pegs: usize,
+        colours: u8,
+        max: Option<u32>,
+    },
+    Marks(Marks),
+    MastermindSolverGuess {
+        guess: &'a str,
+        marks: Marks,
+        candidates: usize,
+    },
+    KnuthReport {
+        games: u32,
+        pegs: usize,
+        colours: u8,
+        average: f64,
+        max: u32,
+    },
+    KnuthBound {
+        ok: bool,
+    },
+    ScoreNotSaved(&'a str),
+    NoScores,
+    ScoresRemoved(usize),
+    ScoresHeading(&'a str),
+    ScoreRow {
+        rank: usize,
+        player: &'a str,
+        points: u32,
+        attempts: u32,
+        seconds: f64,
+        low: u32,
+        high: u32,
+        date: &'a str,
+    },
+    BenchHeader {
+        games: u32,
+        low: u32,
+        high: u32,
+        seed: u64,
+    },
+    BenchStrategy {
+        strategy: &'a str,
+        wins: u32,
+        games: u32,
+        average: f64,
+        max: u32,
+    },
+    BenchBound {
+        bound: u32,
+        ok: bool,
+    },
+    Replayed {
+        file: &'a str,
+        turns: usize,
+        seed: u64,
+    },
+    Listening(&'a str),
+}
+
+fn en(msg: Msg) -> String {
+    match msg {
+        Msg::Welcome => "Guess the number!".to_string(),
+        Msg::Range { low, high } => format!("The number is between {} and {}.", low, high),
+        Msg::RangeWithAttempts { low, high, max } => format!(
+            "The number is between {} and {}, you have {} attempts.",
+            low, high, max
+        ),
+        Msg::HintIntro => "Type 'hint' to see the hints you can buy.".to_string(),
+        Msg::Prompt => "Please input your guess...".to_string(),
+        Msg::YouGuessed(input) => format!("You guessed: {}", input),
+        Msg::Feedback(Feedback::TooSmall) => "too small!".to_string(),
+        Msg::Feedback(Feedback::TooBig) => "too big!".to_string(),
+        Msg::Feedback(Feedback::Correct) => "you win!".to_string(),
+        Msg::Feedback(Feedback::OutOfRange { low, high }) => {
+            format!("please guess between {} and {}", low, high)
+        }
+        Msg::Feedback(Feedback::GameOver) => "the game is already over".to_string(),
+        Msg::Input(e) => match e {
+            InputError::Empty => "please type a number before pressing enter".to_string(),
+            InputError::NotANumber(input) => format!("'{}' is not a number", input),
+            InputError::Negative(input) => {
+                format!("{} is negative, the number is never below zero", input)
+            }
+            InputError::Overflow(input) => format!("{} is too large to be a guess", input),
+            InputError::OutOfRange { value, low, high } => format!(
+                "{} is out of range, please guess between {} and {}",
+                value, low, high
+            ),
+            InputError::Eof => "input closed, ending the game".to_string(),
+        },
+        Msg::HintHelp => "hints: 'hint parity' (-50), 'hint div <n>' (-75), \
+                          'hint digits' (-100), 'hint warmer' (-25)"
+            .to_string(),
+        Msg::HintUsed(hint, result) => format!("hint ({}): {}", hint, en(Msg::HintText(result))),
+        Msg::HintText(result) => match result {
+            HintResult::Parity { even: true } => "the number is even".to_string(),
+            HintResult::Parity { even: false } => "the number is odd".to_string(),
+            HintResult::Divisible {
+                divisor,
+                divisible: true,
+            } => {
+                format!("the number is divisible by {}", divisor)
+            }
+            HintResult::Divisible {
+                divisor,
+                divisible: false,
+            } => {
+                format!("the number is not divisible by {}", divisor)
+            }
+            HintResult::DigitSum(sum) => format!("the digits of the number add up to {}", sum),
+            HintResult::Temperature(Ordering::Less) => {
+                "warmer, your last guess was closer".to_string()
+            }
+            HintResult::Temperature(Ordering::Greater) => {
+                "colder, your last guess was further away".to_string()
+            }
+            HintResult::Temperature(Ordering::Equal) => {
+                "same distance as the guess before".to_string()
+            }
+            HintResult::NeedTwoGuesses => {
+                "guess at least twice before asking warmer/colder".to_string()
+            }
+        },
+        Msg::NoMoreHints => "the game is over, no more hints".to_string(),
+        Msg::Won { attempts } => format!("You found the number in {} attempts.", attempts),
+        Msg::Lost { secret, attempts } => format!(
+            "you lose! the number was {} ({} attempts)",
+            secret, attempts
+        ),
+        Msg::Unfinished { attempts } => format!("Game not finished after {} attempts.", attempts),
+        Msg::SolverGuess {
+            solver,
+            guess,
+            feedback,
+        } => {
+            format!(
+                "[{}] guesses {}: {}",
+                solver,
+                guess,
+                en(Msg::Feedback(feedback))
+            )
+        }
+        Msg::Score(score) => format!("score: {}", score),
+        Msg::HighScore { rank, difficulty } => {
+            format!("high score: #{} on the {} table", rank, difficulty)
+        }
+        Msg::ReverseIntro { low, high } => format!(
+            "Think of a number between {} and {}, I will guess it!",
+            low, high
+        ),
+        Msg::ReverseAsk(guess) => format!("Is it {}? (higher/lower/correct)", guess),
+        Msg::ReverseBadAnswer => "please answer higher, lower or correct".to_string(),
+        Msg::ReverseGotIt { attempts } => format!("I got it in {} attempts!", attempts),
+        Msg::ReverseGiveUp => "I give up, you win!".to_string(),
+        Msg::Answer(answer) => {
+            let said = match answer.ordering {
+                Ordering::Less => "higher than",
+                Ordering::Greater => "lower than",
+                Ordering::Equal => "equal to",
+            };
+            format!(
+                "answer #{}: your number is {} {}",
+                answer.turn, said, answer.guess
+            )
+        }
+        Msg::Contradiction(c) => match c.earlier {
+            Some(ref earlier) => format!(
+                "you lied! {} contradicts {}",
+                en(Msg::Answer(&c.latest)),
+                en(Msg::Answer(earlier))
+            ),
+            None => format!(
+                "you lied! {} but we agreed the number is between {} and {}",
+                en(Msg::Answer(&c.latest)),
+                c.low,
+                c.high
+            ),
+        },
+        Msg::TuiHelp => "Type a number and press Enter, or 'hint' for help.".to_string(),
+        Msg::TuiWon => "you win! press any key to exit".to_string(),
+        Msg::TuiLost { secret } => {
+            format!("you lose! the number was {}, press any key to exit", secret)
+        }
+        Msg::TuiStats {
+            attempts,
+            remaining,
+            seconds,
+            limit,
+            hint_penalty,
+        } => format!(
+            "attempts: {} ({})   time: {}   hints: -{}",
+            attempts,
+            remaining.map_or_else(|| "unlimited".to_string(), |n| format!("{} left", n)),
+            clock(seconds, limit),
+            hint_penalty
+        ),
+        Msg::TimeLimit { seconds } => format!("You have {} seconds, hurry up!", seconds),
+        Msg::TimeLeft { seconds } => format!("({} seconds left)", seconds),
+        Msg::TimeUp => "Time is up!".to_string(),
+        Msg::Daily { date } => format!(
+            "Daily challenge for {}, everyone gets the same number today.",
+            date
+        ),
+        Msg::TuiHistory => "history".to_string(),
+        Msg::TuiYourGuess => "your guess".to_string(),
+        Msg::DomainRange {
+            kind,
+            low,
+            high,
+            max,
+        } => {
+            let what = match kind {
+                DomainKind::Number => "number",
+                DomainKind::Letter => "letter",
+                DomainKind::Decimal => "decimal",
+                DomainKind::Word => "word",
+            };
+            match max {
+                Some(max) => format!(
+                    "Guess the {}! It is between {} and {}, you have {} attempts.",
+                    what, low, high, max
+                ),
+                None => format!("Guess the {}! It is between {} and {}.", what, low, high),
+            }
+        }
+        Msg::Tolerance(tolerance) => format!("Anything within {} of the answer counts.", tolerance),
+        Msg::DomainFeedback { kind, feedback } => match (kind, feedback) {
+            (DomainKind::Letter, Feedback::TooSmall) => {
+                "too early, it comes later in the alphabet".to_string()
+            }
+            (DomainKind::Letter, Feedback::TooBig) => {
+                "too late, it comes earlier in the alphabet".to_string()
+            }
+            (DomainKind::Word, Feedback::TooSmall) => {
+                "too early, it comes later in the dictionary".to_string()
+            }
+            (DomainKind::Word, Feedback::TooBig) => {
+                "too late, it comes earlier in the dictionary".to_string()
+            }
+            (_, feedback) => en(Msg::Feedback(feedback)),
+        },
+        Msg::Domain(e) => match e {
+            DomainError::Empty => "please type a guess before pressing enter".to_string(),
+            DomainError::Number(e) => en(Msg::Input(e)),
+            DomainError::NotALetter(input) => format!("'{}' is not a single letter", input),
+            DomainError::NotADecimal(input) => format!("'{}' is not a decimal number", input),
+            DomainError::TooPrecise { input, precision } => format!(
+                "{} has too many decimal places, use at most {}",
+                input, precision
+            ),
+            DomainError::UnknownWord(word) => format!("'{}' is not in the dictionary", word),
+            DomainError::NotACode(input) => format!("'{}' should only contain digits", input),
+            DomainError::WrongLength { input, expected } => {
+                format!("'{}' should have exactly {} digits", input, expected)
+            }
+            DomainError::OutOfRange { value, low, high } => format!(
+                "{} is out of range, please guess between {} and {}",
+                value, low, high
+            ),
+            DomainError::EmptyRange => "the range is empty".to_string(),
+            DomainError::RangeTooLarge => "the range has too many values".to_string(),
+            DomainError::MixedPrecision {
+                low,
+                high,
+                tolerance,
+            } => format!(
+                "low, high and tolerance must have the same precision, got {}, {} and {} decimal places",
+                low, high, tolerance
+            ),
+            DomainError::Eof => "input closed, ending the game".to_string(),
+        },
+        Msg::DomainWon { attempts } => format!("You got it in {} attempts.", attempts),
+        Msg::MastermindIntro { pegs, colours, max } => {
+            let intro = format!(
+                "Crack the code! It has {} digits, each from 1 to {}, digits may repeat.",
+                pegs, colours
+            );
+            match max {
+                Some(max) => format!("{} You have {} attempts.", intro, max),
+                None => intro,
+            }
+        }
+        Msg::Marks(marks) => format!("{} exact, {} misplaced", marks.exact, marks.misplaced),
+        Msg::MastermindSolverGuess {
+            guess,
+            marks,
+            candidates,
+        } => format!(
+            "[knuth] guesses {}: {} exact, {} misplaced ({} candidates left)",
+            guess, marks.exact, marks.misplaced, candidates
+        ),
+        Msg::DomainLost { secret, attempts } => format!(
+            "you lose! the answer was {} ({} attempts)",
+            secret, attempts
+        ),
+        Msg::KnuthReport {
+            games,
+            pegs,
+            colours,
+            average,
+            max,
+        } => format!(
+            "knuth over all {} codes ({} pegs, {} colours): average {:.3}, max {}",
+            games, pegs, colours, average, max
+        ),
+        Msg::KnuthBound { ok } => format!("at most 5 guesses: {}", verdict(ok)),
+        Msg::ScoreNotSaved(e) => format!("warning: score not saved: {}", e),
+        Msg::NoScores => "no scores yet".to_string(),
+        Msg::ScoresRemoved(count) => format!("removed {} score(s)", count),
+        Msg::ScoresHeading(difficulty) => format!("== {} ==", difficulty),
+        Msg::ScoreRow {
+            rank,
+            player,
+            points,
+            attempts,
+            seconds,
+            low,
+            high,
+            date,
+        } => format!(
+            "{:>3}. {:<16} {:>5} pts {:>3} attempts {:>8.1}s  {}..={}  {}",
+            rank, player, points, attempts, seconds, low, high, date
+        ),
+        Msg::BenchHeader {
+            games,
+            low,
+            high,
+            seed,
+        } => format!(
+            "{} games per strategy, range {}..={}, seed {}",
+            games, low, high, seed
+        ),
+        Msg::BenchStrategy {
+            strategy,
+            wins,
+            games,
+            average,
+            max,
+        } => format!(
+            "== {} == wins {}/{}, average {:.2}, max {}",
+            strategy, wins, games, average, max
+        ),
+        Msg::BenchBound { bound, ok } => {
+            format!("optimal bound {} attempts: {}", bound, verdict(ok))
+        }
+        Msg::Replayed { file, turns, seed } => format!(
+            "{}: replayed {} turns with seed {}, all feedback matches",
+            file, turns, seed
+        ),
+        Msg::Listening(addr) => format!("guessing_game server listening on {}", addr),
+    }
+}
+
+fn zh_cn(msg: Msg) -> String {
+    match msg {
+        Msg::Welcome => "猜数字游戏开始！".to_string(),
+        Msg::Range { low, high } => format!("数字在 {} 到 {} 之间。", low, high),
+        Msg::RangeWithAttempts { low, high, max } => {
+            format!("数字在 {} 到 {} 之间，你有 {} 次机会。", low, high, max)
+        }
+        Msg::HintIntro => "输入 hint 查看可以购买的提示。".to_string(),
+        Msg::Prompt => "请输入你的猜测……".to_string(),
+        Msg::YouGuessed(input) => format!("你猜的是：{}", input),
+        Msg::Feedback(Feedback::TooSmall) => "太小了！".to_string(),
+        Msg::Feedback(Feedback::TooBig) => "太大了！".to_string(),
+        Msg::Feedback(Feedback::Correct) => "你赢了！".to_string(),
+        Msg::Feedback(Feedback::OutOfRange { low, high }) => {
+            format!("请猜 {} 到 {} 之间的数字", low, high)
+        }
+        Msg::Feedback(Feedback::GameOver) => "游戏已经结束了".to_string(),
+        Msg::Input(e) => match e {
+            InputError::Empty => "请先输入一个数字再按回车".to_string(),
+            InputError::NotANumber(input) => format!("“{}”不是数字", input),
+            InputError::Negative(input) => format!("{} 是负数，答案不会小于零", input),
+            InputError::Overflow(input) => format!("{} 太大了，不能作为猜测", input),
+            InputError::OutOfRange { value, low, high } => {
+                format!("{} 超出了范围，请猜 {} 到 {} 之间的数字", value, low, high)
+            }
+            InputError::Eof => "输入已关闭，游戏结束".to_string(),
+        },
+        Msg::HintHelp => "提示：hint parity 奇偶 (-50)，hint div <n> 整除 (-75)，\
+                          hint digits 各位数字之和 (-100)，hint warmer 冷热 (-25)"
+            .to_string(),
+        Msg::HintUsed(hint, result) => {
+            format!("提示（{}）：{}", hint, zh_cn(Msg::HintText(result)))
+        }
+        Msg::HintText(result) => match result {
+            HintResult::Parity { even: true } => "这个数字是偶数".to_string(),
+            HintResult::Parity { even: false } => "这个数字是奇数".to_string(),
+            HintResult::Divisible {
+                divisor,
+                divisible: true,
+            } => {
+                format!("这个数字能被 {} 整除", divisor)
+            }
+            HintResult::Divisible {
+                divisor,
+                divisible: false,
+            } => {
+                format!("这个数字不能被 {} 整除", divisor)
+            }
+            HintResult::DigitSum(sum) => format!("这个数字各位之和是 {}", sum),
+            HintResult::Temperature(Ordering::Less) => "更热了，这次比上次更接近".to_string(),
+            HintResult::Temperature(Ordering::Greater) => "更冷了，这次比上次更远".to_string(),
+            HintResult::Temperature(Ordering::Equal) => "和上次的距离一样".to_string(),
+            HintResult::NeedTwoGuesses => "至少猜两次才能比较冷热".to_string(),
+        },
+        Msg::NoMoreHints => "游戏已经结束，不能再使用提示".to_string(),
+        Msg::Won { attempts } => format!("你用了 {} 次猜中了数字。", attempts),
+        Msg::Lost { secret, attempts } => {
+            format!("你输了！答案是 {}（猜了 {} 次）", secret, attempts)
+        }
+        Msg::Unfinished { attempts } => format!("猜了 {} 次，游戏还没有结束。", attempts),
+        Msg::SolverGuess {
+            solver,
+            guess,
+            feedback,
+        } => {
+            format!(
+                "[{}] 猜 {}：{}",
+                solver,
+                guess,
+                zh_cn(Msg::Feedback(feedback))
+            )
+        }
+        Msg::Score(score) => format!(
+            "得分：{} - {} x {} 次 - {} x {} 秒 - {} 提示 = {}",
+            BASE_POINTS,
+            ATTEMPT_COST,
+            score.attempts,
+            SECOND_COST,
+            score.seconds,
+            score.hint_penalty,
+            score.total
+        ),
+        Msg::HighScore { rank, difficulty } => {
+            format!("高分榜：{} 难度第 {} 名", difficulty, rank)
+        }
+        Msg::ReverseIntro { low, high } => {
+            format!("在 {} 到 {} 之间想一个数字，我来猜！", low, high)
+        }
+        Msg::ReverseAsk(guess) => format!("是 {} 吗？（大/小/对）", guess),
+        Msg::ReverseBadAnswer => "请回答 大、小 或者 对".to_string(),
+        Msg::ReverseGotIt { attempts } => format!("我用了 {} 次就猜中了！", attempts),
+        Msg::ReverseGiveUp => "我认输，你赢了！".to_string(),
+        Msg::Answer(answer) => {
+            let said = match answer.ordering {
+                Ordering::Less => "大于",
+                Ordering::Greater => "小于",
+                Ordering::Equal => "等于",
+            };
+            format!(
+                "第 {} 次回答：你的数字{} {}",
+                answer.turn, said, answer.guess
+            )
+        }
+        Msg::Contradiction(c) => match c.earlier {
+            Some(ref earlier) => format!(
+                "你说谎了！{} 和 {} 矛盾",
+                zh_cn(Msg::Answer(&c.latest)),
+                zh_cn(Msg::Answer(earlier))
+            ),
+            None => format!(
+                "你说谎了！{}，但我们约定数字在 {} 到 {} 之间",
+                zh_cn(Msg::Answer(&c.latest)),
+                c.low,
+                c.high
+            ),
+        },
+        Msg::TuiHelp => "输入数字后按回车，输入 hint 查看提示。".to_string(),
+        Msg::TuiWon => "你赢了！按任意键退出".to_string(),
+        Msg::TuiLost { secret } => format!("你输了！答案是 {}，按任意键退出", secret),
+        Msg::TuiStats {
+            attempts,
+            remaining,
+            seconds,
+            limit,
+            hint_penalty,
+        } => format!(
+            "次数：{}（{}）   用时：{}   提示：-{}",
+            attempts,
+            remaining.map_or_else(|| "不限".to_string(), |n| format!("剩余 {}", n)),
+            clock(seconds, limit),
+            hint_penalty
+        ),
+        Msg::TimeLimit { seconds } => format!("限时 {} 秒，抓紧时间！", seconds),
+        Msg::TimeLeft { seconds } => format!("（还剩 {} 秒）", seconds),
+        Msg::TimeUp => "时间到！".to_string(),
+        Msg::Daily { date } => format!("{} 的每日挑战，今天所有人猜的都是同一个数字。", date),
+        Msg::TuiHistory => "历史".to_string(),
+        Msg::TuiYourGuess => "你的猜测".to_string(),
+        Msg::DomainRange {
+            kind,
+            low,
+            high,
+            max,
+        } => {
+            let what = match kind {
+                DomainKind::Number => "数字",
+                DomainKind::Letter => "字母",
+                DomainKind::Decimal => "小数",
+                DomainKind::Word => "单词",
+            };
+            match max {
+                Some(max) => format!(
+                    "猜{}游戏开始！答案在 {} 到 {} 之间，你有 {} 次机会。",
+                    what, low, high, max
+                ),
+                None => format!("猜{}游戏开始！答案在 {} 到 {} 之间。", what, low, high),
+            }
+        }
+        Msg::Tolerance(tolerance) => format!("和答案相差不超过 {} 就算猜中。", tolerance),
+        Msg::DomainFeedback { kind, feedback } => match (kind, feedback) {
+            (DomainKind::Letter, Feedback::TooSmall) => {
+                "太靠前了，答案在字母表里更靠后".to_string()
+            }
+            (DomainKind::Letter, Feedback::TooBig) => "太靠后了，答案在字母表里更靠前".to_string(),
+            (DomainKind::Word, Feedback::TooSmall) => "太靠前了，答案在词典里更靠后".to_string(),
+            (DomainKind::Word, Feedback::TooBig) => "太靠后了，答案在词典里更靠前".to_string(),
+            (_, feedback) => zh_cn(Msg::Feedback(feedback)),
+        },
+        Msg::Domain(e) => match e {
+            DomainError::Empty => "请先输入猜测再按回车".to_string(),
+            DomainError::Number(e) => zh_cn(Msg::Input(e)),
+            DomainError::NotALetter(input) => format!("“{}”不是单个字母", input),
+            DomainError::NotADecimal(input) => format!("“{}”不是小数", input),
+            DomainError::TooPrecise { input, precision } => {
+                format!("{} 的小数位数太多了，最多 {} 位", input, precision)
+            }
+            DomainError::UnknownWord(word) => format!("词典里没有“{}”", word),
+            DomainError::NotACode(input) => format!("“{}”只能包含数字", input),
+            DomainError::WrongLength { input, expected } => {
+                format!("“{}”必须正好是 {} 位数字", input, expected)
+            }
+            DomainError::OutOfRange { value, low, high } => {
+                format!("{} 超出了范围，请猜 {} 到 {} 之间的值", value, low, high)
+            }
+            DomainError::EmptyRange => "范围是空的".to_string(),
+            DomainError::RangeTooLarge => "范围里的值太多了".to_string(),
+            DomainError::MixedPrecision {
+                low,
+                high,
+                tolerance,
+            } => format!(
+                "最小值、最大值和误差的小数位数必须相同，现在分别是 {}、{} 和 {} 位",
+                low, high, tolerance
+            ),
+            DomainError::Eof => "输入已关闭，游戏结束".to_string(),
+        },
+        Msg::DomainWon { attempts } => format!("你用了 {} 次猜中了答案。", attempts),
+        Msg::MastermindIntro { pegs, colours, max } => {
+            let intro = format!(
+                "破解密码！密码有 {} 位，每位是 1 到 {} 的数字，可以重复。",
+                pegs, colours
+            );
+            match max {
+                Some(max) => format!("{}你有 {} 次机会。", intro, max),
+                None => intro,
+            }
+        }
+        Msg::Marks(marks) => format!(
+            "{} 个位置正确，{} 个数字正确但位置不对",
+            marks.exact, marks.misplaced
+        ),
+        Msg::MastermindSolverGuess {
+            guess,
+            marks,
+            candidates,
+        } => format!(
+            "[knuth] 猜 {}：{} 个位置正确，{} 个位置不对（还剩 {} 种可能）",
+            guess, marks.exact, marks.misplaced, candidates
+        ),
+        Msg::DomainLost { secret, attempts } => {
+            format!("你输了！答案是 {}（猜了 {} 次）", secret, attempts)
+        }
+        Msg::KnuthReport {
+            games,
+            pegs,
+            colours,
+            average,
+            max,
+        } => format!(
+            "Knuth解法跑完全部 {} 种密码（{} 位，{} 种数字）：平均 {:.3} 次，最多 {} 次",
+            games, pegs, colours, average, max
+        ),
+        Msg::KnuthBound { ok } => format!("最多 5 次：{}", verdict(ok)),
+        Msg::ScoreNotSaved(e) => format!("警告：分数没有保存：{}", e),
+        Msg::NoScores => "还没有分数记录".to_string(),
+        Msg::ScoresRemoved(count) => format!("删除了 {} 条分数记录", count),
+        Msg::ScoresHeading(difficulty) => format!("== {} 难度 ==", difficulty),
+        Msg::ScoreRow {
+            rank,
+            player,
+            points,
+            attempts,
+            seconds,
+            low,
+            high,
+            date,
+        } => format!(
+            "{:>3}. {:<16} {:>5} 分 {:>3} 次 {:>8.1} 秒  {}..={}  {}",
+            rank, player, points, attempts, seconds, low, high, date
+        ),
+        Msg::BenchHeader {
+            games,
+            low,
+            high,
+            seed,
+        } => format!(
+            "每种策略 {} 局，范围 {}..={}，种子 {}",
+            games, low, high, seed
+        ),
+        Msg::BenchStrategy {
+            strategy,
+            wins,
+            games,
+            average,
+            max,
+        } => format!(
+            "== {} == 赢了 {}/{} 局，平均 {:.2} 次，最多 {} 次",
+            strategy, wins, games, average, max
+        ),
+        Msg::BenchBound { bound, ok } => format!("最优上界 {} 次：{}", bound, verdict(ok)),
+        Msg::Replayed { file, turns, seed } => format!(
+            "{}：用种子 {} 重放了 {} 步，每一步的反馈都一致",
+            file, seed, turns
+        ),
+        Msg::Listening(addr) => format!("猜数字服务正在监听 {}", addr),
+    }
+}
+
+// 检查结果用OK和FAILED，两种语言一样，方便脚本匹配
+fn verdict(ok: bool) -> &'static str {
+    if ok {
+        "OK"
+    } else {
+        "FAILED"
+    }
+}
+
+// 计时显示成分:秒，限时模式下同时显示总时间
+fn clock(seconds: u64, limit: Option<u64>) -> String {
+    let elapsed = format!("{:02}:{:02}", seconds / 60, seconds % 60);
+    match limit {
+        Some(limit) => format!("{} / {:02}:{:02}", elapsed, limit / 60, limit % 60),
+        None => elapsed,
+    }
+}
@@ -0,0 +1,65 @@
+use crate::config::GameConfig;
+use crate::i18n::{Lang, Msg};
+use std::fmt;
+use std::num::IntErrorKind;
+
+// 玩家输入无法作为一次猜测时的错误，每种错误都有一条展示给玩家的提示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    // 只输入了空白
+    Empty,
+    // 包含数字以外的字符
+    NotANumber(String),
+    // 负数，猜测的数字必须是自然数
+    Negative(String),
+    // 超出了u32能表示的范围
+    Overflow(String),
+    // 是合法的数字，但不在本局游戏的范围内
+    OutOfRange { value: u32, low: u32, high: u32 },
+    // 输入已经结束，比如标准输入被关闭
+    Eof,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Lang::En.text(Msg::Input(self)))
+    }
+}
+
+impl std::error::Error for InputError {}
+
+// 把一行输入解析成本局游戏范围内的猜测
+pub fn parse_guess(input: &str, config: &GameConfig) -> Result<u32, InputError> {
+    // trim会去掉首尾的空白字符，比如空格、换行等等
+    let input = input.trim();
+
+    // 使用match匹配parse返回的结果，根据错误的种类给出不同的提示
+    let value: u32 = match input.parse() {
+        Ok(num) => num,
+        Err(e) => {
+            return Err(match e.kind() {
+                IntErrorKind::Empty => InputError::Empty,
+                IntErrorKind::PosOverflow => InputError::Overflow(input.to_string()),
+                // u32不接受负号，所以负数会被当成非法字符，这里单独识别出来
+                _ if is_negative_number(input) => InputError::Negative(input.to_string()),
+                _ => InputError::NotANumber(input.to_string()),
+            })
+        }
+    };
+
+    if !config.contains(value) {
+        return Err(InputError::OutOfRange {
+            value,
+            low: config.low,
+            high: config.high,
+        });
+    }
+    Ok(value)
+}
+
+fn is_negative_number(input: &str) -> bool {
+    match input.strip_prefix('-') {
+        Some(digits) => !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    }
+}
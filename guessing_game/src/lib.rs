@@ -0,0 +1,39 @@
+// guessing_game 的核心逻辑放在库里，main 只负责把标准输入输出接到前端上
+// 这样同一套游戏循环可以跑在命令行、测试脚本或者网络服务后面
+pub mod bench;
+pub mod config;
+pub mod domain;
+pub mod frontend;
+pub mod game;
+pub mod hint;
+pub mod i18n;
+pub mod input;
+pub mod mastermind;
+pub mod reverse;
+pub mod scores;
+pub mod scoring;
+pub mod secret;
+pub mod server;
+pub mod solver;
+pub mod transcript;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+pub use bench::BenchReport;
+pub use config::{ConfigError, Difficulty, GameConfig};
+pub use domain::{
+    play_domain, Decimal, Decimals, Domain, DomainError, DomainGame, DomainKind, Letters, Words,
+};
+pub use frontend::{play, summary, Frontend, LineFrontend, ScriptedFrontend, TimedFrontend};
+pub use game::{Feedback, GameState, GuessingGame};
+pub use hint::{is_hint_request, parse_hint, Hint, HintResult};
+pub use i18n::{Lang, Msg};
+pub use input::{parse_guess, InputError};
+pub use mastermind::{play_mastermind, Code, KnuthSolver, Marks, MastermindGame, Rules};
+pub use reverse::{play_reverse, Answer, Contradiction, ReverseGame};
+pub use scores::{ScoreEntry, ScoreError, ScoreFilter, ScoreTable};
+pub use scoring::Score;
+pub use secret::{DailySecret, FixedSecret, RandomSecret, SecretSource, SeededSecret};
+pub use server::{Server, ServerConfig, ServerHandle};
+pub use solver::{solve, BinarySearch, Linear, RandomGuess, Solver};
+pub use transcript::{RecordingFrontend, Transcript, TranscriptError, Turn};
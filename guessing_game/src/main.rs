@@ -1,53 +1,466 @@
-use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+mod cli;
+
+use chrono::Local;
+use cli::{
+    BenchOptions, Command, DomainOptions, MastermindOptions, PlayOptions, ReplayOptions,
+    ReverseOptions, ScoresOptions, ServeOptions,
+};
+use guessing_game::{
+    bench, mastermind, play, play_domain, play_mastermind, play_reverse, solve, solver, summary,
+    transcript, DailySecret, Decimal, Decimals, Domain, DomainError, DomainGame, DomainKind,
+    GameConfig, GameState, GuessingGame, KnuthSolver, Lang, Letters, LineFrontend, MastermindGame,
+    Msg, RandomSecret, RecordingFrontend, ReverseGame, ScoreEntry, ScoreTable, SecretSource,
+    SeededSecret, Server, TimedFrontend, Transcript, Words,
+};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use std::{env, io, process};
 
 fn main() {
-    // 使用println!宏来输出字符串到控制端
-    println!("Guess the number!");
-
-    // 调用rand方法生产范围内的随机数
-    let secret_number = rand::thread_rng().gen_range(1, 101);
-    // println!("the secret number is {}", secret_number);
-
-    loop {
-        println!("Please input your guess...");
-
-        // 使用内置的String调用关联函数(不属于某实例，而是直接通过域调用)产生空白的字符串
-        // 返回值绑定到可变的变量，mut guess可变表示可以修改
-        // 对于rust默认时不可变，rust会检查是否会修改内存
-        let mut guess = String::new();
-
-        // 使用io::stdin的关联函数来读取控制行的输入，必须传入可变的引用
-        // 引用会降低在函数间传递的拷贝开销，但是rust不同的事，引用也是默认不可变，无法修改内存
-        // 这时候必须指定mut &方式
-        io::stdin().read_line(&mut guess)
-            // 在rust错误处理也不是以异常为主，而是主动判断和处理错误
-            // 不过一个好处是，rust可以通过简单的链式调用来处理，返回io::Result
-            // Result是一个枚举类型，枚举由一系列固定值组合而成，枚举的值也被称为变体，比如Ok和Err
-            // 如下的expect函数当枚举是Err的时候回panic中断程序打印传入msg和错误信息，Ok的时候会返回结果
-            .expect("Failed");
-
-        // 使用println!宏来输出字符串到控制端，可以通过{}来输出参数
-        println!("You guessed: {}", guess);
-
-        // rust允许使用同一个变量名 再次定义为其他类型，是rust的隐藏机制
-        // trim会去掉首尾的空白字符，比如空格、换行等等
-        // 使用match匹配parse返回的结果，如果报错会直接跳过忽略
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
+    let command = match cli::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Play(options) => run_play(options),
+        Command::Scores(options) => run_scores(options),
+        Command::Bench(options) => run_bench(options),
+        Command::Reverse(options) => run_reverse(options),
+        Command::Serve(options) => run_serve(options),
+        Command::Replay(options) => run_replay(options),
+        Command::Domain(options) => run_domain(options),
+        Command::Mastermind(options) => run_mastermind(options),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run_play(options: PlayOptions) -> Result<(), Box<dyn std::error::Error>> {
+    // 记录游戏时必须能复现秘密数字，没有指定种子就随机生成一个写进记录里
+    let seed = match options.record {
+        Some(_) => Some(options.seed.unwrap_or_else(rand::random)),
+        None => options.seed,
+    };
+
+    // 每日挑战由日期决定秘密数字，指定种子时使用可复现的伪随机数，否则每局都是真正的随机数
+    let today = Local::now().date_naive();
+    let mut source: Box<dyn SecretSource> = match seed {
+        _ if options.daily => Box::new(DailySecret::new(today)),
+        Some(seed) => Box::new(SeededSecret::new(seed)),
+        None => Box::new(RandomSecret),
+    };
+
+    let mut game = GuessingGame::from_source(options.config, source.as_mut());
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    if options.daily {
+        let date = today.format("%Y-%m-%d").to_string();
+        println!("{}", lang.text(Msg::Daily { date: &date }));
+    }
+
+    // 电脑自己玩的时候只输出过程，不记录到高分表
+    if let Some(name) = options.solver {
+        let mut solver =
+            solver::by_name(&name, options.seed.unwrap_or(0)).ok_or("unknown solver")?;
+        solve(&mut game, solver.as_mut(), |guess, feedback| {
+            println!(
+                "{}",
+                lang.text(Msg::SolverGuess {
+                    solver: &name,
+                    guess,
+                    feedback,
+                })
+            );
+        });
+        println!("{}", summary(&game, lang));
+        return Ok(());
+    }
+
+    // 游戏逻辑都在库里，这里只是把标准输入输出接到基于行的前端上
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let line_frontend = || LineFrontend::new(stdin.lock(), stdout.lock()).with_lang(lang);
+
+    let started = Instant::now();
+    let state = match (options.record, seed, options.time_limit) {
+        #[cfg(feature = "tui")]
+        _ if options.tui => guessing_game::tui::run(&mut game, lang, options.time_limit)?,
+        // 限时模式在后台线程读输入，这样时间到了不用等玩家回车就能结束
+        (_, _, Some(limit)) => {
+            println!(
+                "{}",
+                lang.text(Msg::TimeLimit {
+                    seconds: limit.as_secs()
+                })
+            );
+            let mut timed =
+                TimedFrontend::new(io::BufReader::new(io::stdin()), io::stdout(), limit)
+                    .with_lang(lang);
+            play(&mut game, &mut timed)?
+        }
+        (Some(path), Some(seed), None) => {
+            let transcript = Transcript::new(seed, options.config, Local::now().to_rfc3339());
+            let mut recorder = RecordingFrontend::new(line_frontend(), transcript);
+            let state = play(&mut game, &mut recorder)?;
+            recorder.into_transcript().save(path)?;
+            state
+        }
+        _ => play(&mut game, &mut line_frontend())?,
+    };
+    if state != GameState::Won {
+        return Ok(());
+    }
+
+    // 获胜后记录到高分表，分数文件出错不影响这局游戏的结果
+    // 每日挑战和限时模式单独排名，例如daily-2024-05-01-normal、hard-60s
+    let config = game.config();
+    let mut difficulty = config.difficulty_name().to_string();
+    if options.daily {
+        difficulty = format!("daily-{}-{}", today.format("%Y-%m-%d"), difficulty);
+    }
+    if let Some(limit) = options.time_limit {
+        difficulty = format!("{}-{}s", difficulty, limit.as_secs());
+    }
+    let entry = ScoreEntry {
+        player: options.player.unwrap_or_else(default_player),
+        difficulty,
+        low: config.low,
+        high: config.high,
+        attempts: game.attempts(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        hint_penalty: game.hint_penalty(),
+        date: Local::now().to_rfc3339(),
+    };
+    println!("{}", lang.text(Msg::Score(&entry.score())));
+    match record_score(options.scores_file, entry) {
+        Ok((rank, difficulty)) => println!(
+            "{}",
+            lang.text(Msg::HighScore {
+                rank,
+                difficulty: &difficulty,
+            })
+        ),
+        Err(e) => eprintln!("{}", lang.text(Msg::ScoreNotSaved(&e.to_string()))),
+    }
+    Ok(())
+}
+
+// 保存到高分表，返回这条记录在同难度中的排名
+fn record_score(
+    path: Option<PathBuf>,
+    entry: ScoreEntry,
+) -> Result<(usize, String), Box<dyn std::error::Error>> {
+    let path = scores_path(path)?;
+    let mut table = ScoreTable::load(&path)?;
+    table.add(entry.clone());
+    table.save(&path)?;
+    let rank = table.rank(&entry).unwrap_or_else(|| table.len());
+    Ok((rank, entry.difficulty))
+}
+
+fn run_scores(options: ScoresOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let path = scores_path(options.scores_file)?;
+    let mut table = ScoreTable::load(&path)?;
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+
+    if options.reset {
+        let removed = table.reset(&options.filter);
+        table.save(&path)?;
+        println!("{}", lang.text(Msg::ScoresRemoved(removed)));
+        return Ok(());
+    }
+
+    // 指定难度时只列出这一个难度，否则每个难度分别列出前N名
+    let difficulties: Vec<String> = match &options.filter.difficulty {
+        Some(d) => vec![d.clone()],
+        None => table.difficulties().into_iter().map(String::from).collect(),
+    };
+    if difficulties.is_empty() {
+        println!("{}", lang.text(Msg::NoScores));
+    }
+    for difficulty in difficulties {
+        let mut filter = options.filter.clone();
+        filter.difficulty = Some(difficulty.clone());
+
+        println!("{}", lang.text(Msg::ScoresHeading(&difficulty)));
+        for (i, entry) in table.top(&filter, options.top).iter().enumerate() {
+            let date = entry.date_time().map_or_else(
+                || entry.date.clone(),
+                |d| d.format("%Y-%m-%d %H:%M").to_string(),
+            );
+            println!(
+                "{}",
+                lang.text(Msg::ScoreRow {
+                    rank: i + 1,
+                    player: &entry.player,
+                    points: entry.score().total,
+                    attempts: entry.attempts,
+                    seconds: entry.elapsed_ms as f64 / 1000.0,
+                    low: entry.low,
+                    high: entry.high,
+                    date: &date,
+                })
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_replay(options: ReplayOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    let recorded = Transcript::load(&options.file)?;
+    let turns = transcript::verify(&recorded)?;
+    println!(
+        "{}",
+        lang.text(Msg::Replayed {
+            file: &options.file.display().to_string(),
+            turns,
+            seed: recorded.seed,
+        })
+    );
+    Ok(())
+}
 
-        // match 表达式由多个分支组成，每个分支包含一个用于匹配的模式，以及匹配到的执行代码
-        // 匹配代码执行完后就会退出，不再继续执行
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("too small!"),
-            Ordering::Greater => println!("too big!"),
-            Ordering::Equal => {
-                println!("you win!");
-                break;
-            },
+fn run_reverse(options: ReverseOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut solver = solver::by_name(&options.solver, options.seed).ok_or("unknown solver")?;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    let mut frontend = LineFrontend::new(stdin.lock(), stdout.lock()).with_lang(lang);
+
+    let mut game = ReverseGame::new(options.config);
+    play_reverse(&mut game, solver.as_mut(), &mut frontend)?;
+    Ok(())
+}
+
+fn run_domain(options: DomainOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let low = options.low.as_deref();
+    let high = options.high.as_deref();
+    match options.kind {
+        DomainKind::Number => {
+            let mut config = GameConfig::default();
+            if let Some(low) = low {
+                config.set("low", low)?;
+            }
+            if let Some(high) = high {
+                config.set("high", high)?;
+            }
+            config.validate()?;
+            play_in_domain(config, &options)
+        }
+        DomainKind::Letter => {
+            let letter = |value: Option<&str>, default: char| match value {
+                None => Ok(default),
+                Some(v) if v.chars().count() == 1 => Ok(v.chars().next().unwrap_or(default)),
+                Some(v) => Err(DomainError::NotALetter(v.to_string())),
+            };
+            let letters = Letters::new(letter(low, 'a')?, letter(high, 'z')?)?;
+            play_in_domain(letters, &options)
+        }
+        DomainKind::Decimal => {
+            let decimal = |value: Option<&str>, default: &str| {
+                Decimal::parse(value.unwrap_or(default), options.precision)
+            };
+            let decimals = Decimals::new(
+                decimal(low, "0")?,
+                decimal(high, "100")?,
+                decimal(options.tolerance.as_deref(), "0")?,
+            )?;
+            play_in_domain(decimals, &options)
+        }
+        DomainKind::Word => {
+            if low.is_some() || high.is_some() {
+                return Err("the words domain takes its range from the dictionary".into());
+            }
+            let words = match &options.words {
+                Some(path) => Words::load(path)?,
+                None => Words::default(),
+            };
+            play_in_domain(words, &options)
+        }
+    }
+}
+
+fn play_in_domain<D: Domain>(
+    domain: D,
+    options: &DomainOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut source: Box<dyn SecretSource> = match options.seed {
+        Some(seed) => Box::new(SeededSecret::new(seed)),
+        None => Box::new(RandomSecret),
+    };
+    let mut game = DomainGame::from_source(domain, options.max_attempts, source.as_mut());
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    let mut frontend = LineFrontend::new(stdin.lock(), stdout.lock()).with_lang(lang);
+    play_domain(&mut game, &mut frontend)?;
+    Ok(())
+}
+
+fn run_mastermind(options: MastermindOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let rules = options.rules;
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+
+    if options.verify {
+        let report = mastermind::bench_knuth(rules);
+        println!(
+            "{}",
+            lang.text(Msg::KnuthReport {
+                games: report.games,
+                pegs: rules.pegs,
+                colours: rules.colours,
+                average: report.average(),
+                max: report.max_attempts,
+            })
+        );
+        print_histogram(&report.histogram);
+        if report.wins != report.games {
+            let msg = format!("knuth solved only {} of {} codes", report.wins, report.games);
+            return Err(msg.into());
+        }
+        // 经典规则下Knuth证明了最多5次
+        if (rules.pegs, rules.colours) == (4, 6) {
+            let ok = report.max_attempts <= 5;
+            println!("{}", lang.text(Msg::KnuthBound { ok }));
+            if !ok {
+                return Err("knuth needed more than 5 guesses on the classic rules".into());
+            }
+        }
+        return Ok(());
+    }
+
+    let mut source: Box<dyn SecretSource> = match options.seed {
+        Some(seed) => Box::new(SeededSecret::new(seed)),
+        None => Box::new(RandomSecret),
+    };
+    let mut game = MastermindGame::from_source(rules, source.as_mut());
+
+    if options.solver {
+        let mut solver = KnuthSolver::new(rules);
+        while game.state() == GameState::Playing {
+            let guess = solver.next_guess();
+            let marks = match game.guess(guess.clone()) {
+                Some(marks) => marks,
+                None => break,
+            };
+            solver.record(&guess, marks);
+            println!(
+                "{}",
+                lang.text(Msg::MastermindSolverGuess {
+                    guess: &guess.to_string(),
+                    marks,
+                    candidates: solver.candidates(),
+                })
+            );
+        }
+        println!("{}", mastermind::mastermind_summary(&game, lang));
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut frontend = LineFrontend::new(stdin.lock(), stdout.lock()).with_lang(lang);
+    play_mastermind(&mut game, &mut frontend)?;
+    Ok(())
+}
+
+fn run_serve(options: ServeOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let source: Box<dyn SecretSource + Send> = match options.seed {
+        Some(seed) => Box::new(SeededSecret::new(seed)),
+        None => Box::new(RandomSecret),
+    };
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    let server = Server::bind(&options.addr, options.config, source)?;
+    let addr = server.local_addr()?.to_string();
+    println!("{}", lang.text(Msg::Listening(&addr)));
+    server.run()?;
+    Ok(())
+}
+
+fn run_bench(options: BenchOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let strategies: Vec<String> = if options.strategies.is_empty() {
+        solver::SOLVERS.iter().map(|s| s.to_string()).collect()
+    } else {
+        options.strategies
+    };
+    let config = options.config;
+    let bound = bench::optimal_bound(&config);
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+
+    println!(
+        "{}",
+        lang.text(Msg::BenchHeader {
+            games: options.games,
+            low: config.low,
+            high: config.high,
+            seed: options.seed,
+        })
+    );
+    for name in strategies {
+        let mut solver = solver::by_name(&name, options.seed).ok_or("unknown solver")?;
+        let report = bench::run(config, options.seed, options.games, solver.as_mut());
+
+        println!();
+        println!(
+            "{}",
+            lang.text(Msg::BenchStrategy {
+                strategy: report.strategy,
+                wins: report.wins,
+                games: report.games,
+                average: report.average(),
+                max: report.max_attempts,
+            })
+        );
+        print_histogram(&report.histogram);
+        if report.strategy == "binary" {
+            let ok = report.wins == report.games && report.max_attempts <= bound;
+            println!("{}", lang.text(Msg::BenchBound { bound, ok }));
+        }
+    }
+    Ok(())
+}
+
+// 直方图最多20行，次数分布太散时合并成等宽的区间，每行按最多的那一栏缩放到40个字符宽
+fn print_histogram(histogram: &BTreeMap<u32, u32>) {
+    let (min, max) = match (histogram.keys().next(), histogram.keys().next_back()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return,
+    };
+    let width = (max - min) / 20 + 1;
+    let mut buckets: BTreeMap<u32, u32> = BTreeMap::new();
+    for (attempts, count) in histogram {
+        *buckets.entry((attempts - min) / width).or_insert(0) += count;
+    }
+
+    let widest = buckets.values().copied().max().unwrap_or(1);
+    for (bucket, count) in buckets {
+        let start = min + bucket * width;
+        let label = if width == 1 {
+            start.to_string()
+        } else {
+            format!("{}-{}", start, start + width - 1)
         };
+        let bar = (u64::from(count) * 40 / u64::from(widest)).max(1) as usize;
+        println!("{:>12} | {:<40} {}", label, "#".repeat(bar), count);
     }
 }
+
+fn scores_path(path: Option<PathBuf>) -> Result<PathBuf, String> {
+    path.or_else(ScoreTable::default_path)
+        .ok_or_else(|| String::from("cannot find the user data directory, use --scores-file"))
+}
+
+// 没有指定玩家名称时使用系统用户名
+fn default_player() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("player"))
+}
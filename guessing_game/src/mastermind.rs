@@ -0,0 +1,365 @@
+// 珠玑妙算（Mastermind / Bulls and Cows）：答案是一串数字，每次猜测只告诉玩家
+// 有几个数字位置也对（exact），有几个数字对但位置不对（misplaced）
+// 输入校验和回合循环沿用domain里的实现，另外带一个Knuth的极小化极大解法
+use crate::bench::BenchReport;
+use crate::domain::{read_guess, DomainError, Turn};
+use crate::frontend::Frontend;
+use crate::game::GameState;
+use crate::i18n::{Lang, Msg};
+use crate::secret::SecretSource;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+
+// 颜色用数字1到9表示，方便在命令行输入
+pub const MAX_COLOURS: u8 = 9;
+// 所有可能的答案最多这么多个，Knuth解法每一步都要比较所有组合的两两反馈，再多就太慢了
+pub const MAX_CODES: usize = 10_000;
+
+// 一组猜测或者答案，每个元素是一个颜色
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code(Vec<u8>);
+
+impl Code {
+    pub fn new(pegs: Vec<u8>) -> Code {
+        Code(pegs)
+    }
+
+    pub fn pegs(&self) -> &[u8] {
+        &self.0
+    }
+
+    // 和答案比较，先数位置和颜色都对的，再按颜色数出现的次数算出位置不对的
+    pub fn marks(&self, secret: &Code) -> Marks {
+        let mut guess_counts = [0u8; MAX_COLOURS as usize + 1];
+        let mut secret_counts = [0u8; MAX_COLOURS as usize + 1];
+        let mut exact = 0;
+        for (&g, &s) in self.0.iter().zip(&secret.0) {
+            if g == s {
+                exact += 1;
+            } else {
+                guess_counts[g as usize] += 1;
+                secret_counts[s as usize] += 1;
+            }
+        }
+        let misplaced = guess_counts
+            .iter()
+            .zip(&secret_counts)
+            .map(|(g, s)| u32::from(*g.min(s)))
+            .sum();
+        Marks { exact, misplaced }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for peg in &self.0 {
+            write!(f, "{}", peg)?;
+        }
+        Ok(())
+    }
+}
+
+// 一次猜测得到的反馈
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Marks {
+    pub exact: u32,
+    pub misplaced: u32,
+}
+
+// 规则：几个位置，几种颜色，最多猜几次
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub pegs: usize,
+    pub colours: u8,
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Rules {
+    // 经典的4个位置6种颜色，Knuth证明了最多5次一定能猜中
+    fn default() -> Rules {
+        Rules {
+            pegs: 4,
+            colours: 6,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+impl Rules {
+    // 把一行输入解析成猜测，允许用空格或逗号分隔，比如 1122 或者 1 1 2 2
+    pub fn parse_code(&self, input: &str) -> Result<Code, DomainError> {
+        let input = input.trim();
+        let digits: String = input
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != ',')
+            .collect();
+        if digits.is_empty() {
+            return Err(DomainError::Empty);
+        }
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DomainError::NotACode(input.to_string()));
+        }
+        if digits.len() != self.pegs {
+            return Err(DomainError::WrongLength {
+                input: input.to_string(),
+                expected: self.pegs,
+            });
+        }
+
+        let pegs: Vec<u8> = digits.bytes().map(|b| b - b'0').collect();
+        if let Some(bad) = pegs.iter().find(|&&p| p == 0 || p > self.colours) {
+            return Err(DomainError::OutOfRange {
+                value: bad.to_string(),
+                low: String::from("1"),
+                high: self.colours.to_string(),
+            });
+        }
+        Ok(Code(pegs))
+    }
+
+    pub fn random_code(&self, source: &mut dyn SecretSource) -> Code {
+        Code(
+            (0..self.pegs)
+                .map(|_| source.next_secret(1, u32::from(self.colours)) as u8)
+                .collect(),
+        )
+    }
+
+    // 所有可能的答案，按字典序排列，数量是colours的pegs次方
+    pub fn all_codes(&self) -> Vec<Code> {
+        let mut codes = vec![Vec::with_capacity(self.pegs)];
+        for _ in 0..self.pegs {
+            codes = codes
+                .into_iter()
+                .flat_map(|code| {
+                    (1..=self.colours).map(move |c| {
+                        let mut next = code.clone();
+                        next.push(c);
+                        next
+                    })
+                })
+                .collect();
+        }
+        codes.into_iter().map(Code).collect()
+    }
+}
+
+pub struct MastermindGame {
+    rules: Rules,
+    secret: Code,
+    attempts: u32,
+    state: GameState,
+    history: Vec<(Code, Marks)>,
+}
+
+impl MastermindGame {
+    pub fn new(rules: Rules, secret: Code) -> MastermindGame {
+        MastermindGame {
+            rules,
+            secret,
+            attempts: 0,
+            state: GameState::Playing,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn from_source(rules: Rules, source: &mut dyn SecretSource) -> MastermindGame {
+        let secret = rules.random_code(source);
+        MastermindGame::new(rules, secret)
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn history(&self) -> &[(Code, Marks)] {
+        &self.history
+    }
+
+    // 游戏结束之前不透露答案
+    pub fn secret(&self) -> Option<&Code> {
+        match self.state {
+            GameState::Playing => None,
+            _ => Some(&self.secret),
+        }
+    }
+
+    // 猜测应该是parse_code得到的，游戏已经结束时返回None
+    pub fn guess(&mut self, code: Code) -> Option<Marks> {
+        if self.state != GameState::Playing {
+            return None;
+        }
+        self.attempts += 1;
+        let marks = code.marks(&self.secret);
+        self.history.push((code, marks));
+
+        if marks.exact as usize == self.rules.pegs {
+            self.state = GameState::Won;
+        } else if self
+            .rules
+            .max_attempts
+            .is_some_and(|max| self.attempts >= max)
+        {
+            self.state = GameState::Lost;
+        }
+        Some(marks)
+    }
+
+    pub fn give_up(&mut self) {
+        if self.state == GameState::Playing {
+            self.state = GameState::Lost;
+        }
+    }
+}
+
+// Knuth的解法：维护和所有反馈一致的候选答案，每次在所有组合里选一个猜测，
+// 使得按反馈分组后最大的一组尽量小，一样小时优先选候选答案，再选字典序最小的
+pub struct KnuthSolver {
+    rules: Rules,
+    all: Vec<Code>,
+    candidates: Vec<Code>,
+}
+
+impl KnuthSolver {
+    pub fn new(rules: Rules) -> KnuthSolver {
+        let all = rules.all_codes();
+        KnuthSolver {
+            rules,
+            candidates: all.clone(),
+            all,
+        }
+    }
+
+    pub fn next_guess(&self) -> Code {
+        choose(&self.rules, &self.all, &self.candidates)
+    }
+
+    // 去掉和这次反馈矛盾的候选答案
+    pub fn record(&mut self, guess: &Code, marks: Marks) {
+        self.candidates.retain(|c| guess.marks(c) == marks);
+    }
+
+    pub fn candidates(&self) -> usize {
+        self.candidates.len()
+    }
+}
+
+// 第一步对所有组合做极小化极大太慢，用Knuth给出的1122这种一半一半的开局
+fn choose(rules: &Rules, all: &[Code], candidates: &[Code]) -> Code {
+    if candidates.len() == 1 {
+        return candidates[0].clone();
+    }
+    if candidates.len() == all.len() {
+        let half = rules.pegs / 2;
+        let colour = |i| if i < half || rules.colours < 2 { 1 } else { 2 };
+        return Code((0..rules.pegs).map(colour).collect());
+    }
+
+    let groups = (rules.pegs + 1) * (rules.pegs + 1);
+    let mut best: Option<(usize, bool, &Code)> = None;
+    for guess in all {
+        let mut sizes = vec![0usize; groups];
+        let mut worst = 0;
+        for secret in candidates {
+            let marks = guess.marks(secret);
+            let i = marks.exact as usize * (rules.pegs + 1) + marks.misplaced as usize;
+            sizes[i] += 1;
+            worst = worst.max(sizes[i]);
+            // 已经不可能比当前最好的更好了
+            if best.is_some_and(|(b, _, _)| worst > b) {
+                break;
+            }
+        }
+        let not_candidate = candidates.binary_search(guess).is_err();
+        if best.is_none_or(|(b, n, _)| (worst, not_candidate) < (b, n)) {
+            best = Some((worst, not_candidate, guess));
+        }
+    }
+    best.map_or_else(|| candidates[0].clone(), |(_, _, guess)| guess.clone())
+}
+
+// 对每一个可能的答案都跑一遍Knuth解法，统计猜中需要的次数
+// 解法是确定的，猜测只取决于之前的反馈，所以按反馈递归地走一遍决策树就够了，
+// 不用每个答案都从头算
+pub fn bench_knuth(rules: Rules) -> BenchReport {
+    let all = rules.all_codes();
+    let mut report = BenchReport {
+        strategy: "knuth",
+        games: all.len() as u32,
+        wins: 0,
+        total_attempts: 0,
+        max_attempts: 0,
+        histogram: BTreeMap::new(),
+    };
+    walk(&rules, &all, all.clone(), 1, &mut report);
+    report
+}
+
+fn walk(rules: &Rules, all: &[Code], candidates: Vec<Code>, depth: u32, report: &mut BenchReport) {
+    let guess = choose(rules, all, &candidates);
+    let mut groups: BTreeMap<Marks, Vec<Code>> = BTreeMap::new();
+    for secret in candidates {
+        groups.entry(guess.marks(&secret)).or_default().push(secret);
+    }
+
+    for (marks, secrets) in groups {
+        if marks.exact as usize == rules.pegs {
+            report.wins += 1;
+            report.total_attempts += u64::from(depth);
+            report.max_attempts = report.max_attempts.max(depth);
+            *report.histogram.entry(depth).or_insert(0) += 1;
+        } else {
+            walk(rules, all, secrets, depth + 1, report);
+        }
+    }
+}
+
+// 和play一样的回合循环，每次猜测后给出exact和misplaced
+pub fn play_mastermind<F: Frontend>(
+    game: &mut MastermindGame,
+    frontend: &mut F,
+) -> io::Result<GameState> {
+    let rules = *game.rules();
+    frontend.say(Msg::MastermindIntro {
+        pegs: rules.pegs,
+        colours: rules.colours,
+        max: rules.max_attempts,
+    })?;
+
+    while game.state() == GameState::Playing {
+        match read_guess(frontend, |input| rules.parse_code(input))? {
+            Turn::Guess(code) => {
+                if let Some(marks) = game.guess(code) {
+                    frontend.say(Msg::Marks(marks))?;
+                }
+            }
+            Turn::Retry => {}
+            Turn::End => game.give_up(),
+        }
+    }
+
+    let text = mastermind_summary(game, frontend.lang());
+    frontend.show(&text)?;
+    Ok(game.state())
+}
+
+pub fn mastermind_summary(game: &MastermindGame, lang: Lang) -> String {
+    let attempts = game.attempts();
+    match (game.state(), game.secret()) {
+        (GameState::Won, _) => lang.text(Msg::DomainWon { attempts }),
+        (GameState::Lost, Some(secret)) => lang.text(Msg::DomainLost {
+            secret: &secret.to_string(),
+            attempts,
+        }),
+        _ => lang.text(Msg::Unfinished { attempts }),
+    }
+}
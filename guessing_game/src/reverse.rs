@@ -0,0 +1,217 @@
+use crate::config::GameConfig;
+use crate::frontend::Frontend;
+use crate::game::GameState;
+use crate::i18n::{Lang, Msg};
+use crate::input::InputError;
+use crate::solver::Solver;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io;
+
+// 玩家对电脑一次猜测的回答，ordering是猜测值相对于玩家心里数字的大小
+// 和正常模式一样: Less表示猜小了，Greater表示猜大了
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer {
+    // 第几次回答，从1开始
+    pub turn: u32,
+    pub guess: u32,
+    pub ordering: Ordering,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Lang::En.text(Msg::Answer(self)))
+    }
+}
+
+// 玩家的回答前后矛盾，剩余范围变成了空
+// earlier为None表示和游戏开始时约定的范围矛盾
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub earlier: Option<Answer>,
+    pub latest: Answer,
+    pub low: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Lang::En.text(Msg::Contradiction(self)))
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+// 反向模式: 玩家心里想一个数字，电脑来猜
+// 这里只记录玩家的回答和剩余的范围，具体怎么猜由Solver决定
+#[derive(Debug)]
+pub struct ReverseGame {
+    config: GameConfig,
+    // 剩余范围的上下界，以及是哪一次回答确定了这个边界
+    // 用i64保存，guess为0或者u32::MAX时加减一不会溢出
+    low: i64,
+    high: i64,
+    low_set_by: Option<Answer>,
+    high_set_by: Option<Answer>,
+    answers: Vec<Answer>,
+    state: GameState,
+}
+
+impl ReverseGame {
+    pub fn new(config: GameConfig) -> ReverseGame {
+        ReverseGame {
+            config,
+            low: i64::from(config.low),
+            high: i64::from(config.high),
+            low_set_by: None,
+            high_set_by: None,
+            answers: Vec::new(),
+            state: GameState::Playing,
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.answers.len() as u32
+    }
+
+    pub fn answers(&self) -> &[Answer] {
+        &self.answers
+    }
+
+    // 根据目前的回答，玩家的数字一定在这个闭区间内，区间为空时返回None
+    pub fn remaining(&self) -> Option<(u32, u32)> {
+        if self.low > self.high {
+            return None;
+        }
+        Some((self.low as u32, self.high as u32))
+    }
+
+    // 记录玩家对guess的回答，回答前后矛盾时返回Contradiction，游戏结束
+    pub fn answer(&mut self, guess: u32, ordering: Ordering) -> Result<GameState, Contradiction> {
+        if self.state != GameState::Playing {
+            return Ok(self.state);
+        }
+        let answer = Answer {
+            turn: self.attempts() + 1,
+            guess,
+            ordering,
+        };
+        self.answers.push(answer);
+
+        let value = i64::from(guess);
+        // 和这次回答矛盾的那个边界，Some(None)表示和最初约定的范围矛盾
+        let conflict = match ordering {
+            Ordering::Less => {
+                if value + 1 > self.low {
+                    self.low = value + 1;
+                    self.low_set_by = Some(answer);
+                }
+                if self.low > self.high {
+                    Some(self.high_set_by)
+                } else {
+                    None
+                }
+            }
+            Ordering::Greater => {
+                if value - 1 < self.high {
+                    self.high = value - 1;
+                    self.high_set_by = Some(answer);
+                }
+                if self.low > self.high {
+                    Some(self.low_set_by)
+                } else {
+                    None
+                }
+            }
+            // 说猜中了，但这个数字之前已经被排除
+            Ordering::Equal if value < self.low => Some(self.low_set_by),
+            Ordering::Equal if value > self.high => Some(self.high_set_by),
+            Ordering::Equal => {
+                self.state = GameState::Won;
+                None
+            }
+        };
+        if let Some(earlier) = conflict {
+            self.state = GameState::Lost;
+            return Err(Contradiction {
+                earlier,
+                latest: answer,
+                low: self.config.low,
+                high: self.config.high,
+            });
+        }
+
+        // 次数用完还没有猜中，电脑认输
+        if self.state == GameState::Playing && self.config.max_attempts == Some(self.attempts()) {
+            self.state = GameState::Lost;
+        }
+        Ok(self.state)
+    }
+}
+
+// 解析玩家的回答，支持英文单词、首字母、符号和中文的 大/小/对
+pub fn parse_answer(input: &str) -> Option<Ordering> {
+    match input.trim().to_lowercase().as_str() {
+        "h" | "higher" | "+" | ">" | "大" => Some(Ordering::Less),
+        "l" | "lower" | "-" | "<" | "小" => Some(Ordering::Greater),
+        "c" | "correct" | "y" | "yes" | "=" | "对" | "是" => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+// 反向模式的游戏循环，电脑每次给出一个猜测，玩家回答higher/lower/correct
+pub fn play_reverse<F, S>(
+    game: &mut ReverseGame,
+    solver: &mut S,
+    frontend: &mut F,
+) -> io::Result<GameState>
+where
+    F: Frontend,
+    S: Solver + ?Sized,
+{
+    let config = *game.config();
+    frontend.say(Msg::ReverseIntro {
+        low: config.low,
+        high: config.high,
+    })?;
+    solver.reset(config.low, config.high);
+
+    let mut last = None;
+    while game.state() == GameState::Playing {
+        let guess = solver.next_guess(last);
+        frontend.say(Msg::ReverseAsk(guess))?;
+
+        let ordering = loop {
+            let input = match frontend.read_input()? {
+                Some(input) => input,
+                None => {
+                    frontend.say(Msg::Input(&InputError::Eof))?;
+                    return Ok(game.state());
+                }
+            };
+            match parse_answer(&input) {
+                Some(ordering) => break ordering,
+                None => frontend.say(Msg::ReverseBadAnswer)?,
+            }
+        };
+
+        match game.answer(guess, ordering) {
+            Ok(GameState::Won) => frontend.say(Msg::ReverseGotIt {
+                attempts: game.attempts(),
+            })?,
+            Ok(GameState::Lost) => frontend.say(Msg::ReverseGiveUp)?,
+            Ok(GameState::Playing) => {}
+            Err(contradiction) => frontend.say(Msg::Contradiction(&contradiction))?,
+        }
+        last = Some(ordering);
+    }
+    Ok(game.state())
+}
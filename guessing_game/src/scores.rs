@@ -0,0 +1,182 @@
+use crate::scoring::Score;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// 高分表中的一条记录，只记录获胜的游戏
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub player: String,
+    // 难度名称，不是预设的范围记为custom
+    pub difficulty: String,
+    pub low: u32,
+    pub high: u32,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+    // 提示一共扣掉的分数，之前版本的文件没有这个字段
+    #[serde(default)]
+    pub hint_penalty: u32,
+    // RFC 3339格式的本地时间
+    pub date: String,
+}
+
+impl ScoreEntry {
+    pub fn score(&self) -> Score {
+        Score::new(self.attempts, Duration::from_millis(self.elapsed_ms), self.hint_penalty)
+    }
+
+    pub fn date_time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.date)
+            .ok()
+            .map(|d| d.with_timezone(&Local))
+    }
+}
+
+// 查询高分表时的过滤条件，None表示不过滤
+#[derive(Debug, Default, Clone)]
+pub struct ScoreFilter {
+    pub difficulty: Option<String>,
+    pub player: Option<String>,
+}
+
+impl ScoreFilter {
+    fn matches(&self, entry: &ScoreEntry) -> bool {
+        self.difficulty.as_ref().is_none_or(|d| *d == entry.difficulty)
+            && self.player.as_ref().is_none_or(|p| *p == entry.player)
+    }
+}
+
+// 分数文件的内容，带上版本号方便以后修改格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreTable {
+    version: u32,
+    entries: Vec<ScoreEntry>,
+}
+
+const SCORE_FILE_VERSION: u32 = 1;
+
+impl Default for ScoreTable {
+    fn default() -> ScoreTable {
+        ScoreTable {
+            version: SCORE_FILE_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl ScoreTable {
+    // 默认的分数文件放在用户数据目录下，比如Linux上的~/.local/share/guessing_game/scores.json
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("guessing_game").join("scores.json"))
+    }
+
+    // 文件不存在时返回空表，第一次玩的时候不需要先创建文件
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ScoreTable, ScoreError> {
+        let content = match fs::read_to_string(path.as_ref()) {
+            Ok(content) => content,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(ScoreTable::default()),
+            Err(e) => return Err(ScoreError::Io(e)),
+        };
+        let table: ScoreTable = serde_json::from_str(&content).map_err(ScoreError::Parse)?;
+        if table.version != SCORE_FILE_VERSION {
+            return Err(ScoreError::Version(table.version));
+        }
+        Ok(table)
+    }
+
+    // 先写到同目录的临时文件并刷到磁盘，再通过rename替换原文件
+    // rename在同一个文件系统内是原子的，写到一半崩溃也只会留下临时文件，原来的分数表不会损坏
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ScoreError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(ScoreError::Parse)?;
+
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+    }
+
+    // 这条记录在同难度中的排名，从1开始
+    pub fn rank(&self, entry: &ScoreEntry) -> Option<usize> {
+        let filter = ScoreFilter {
+            difficulty: Some(entry.difficulty.clone()),
+            player: None,
+        };
+        self.top(&filter, usize::MAX)
+            .iter()
+            .position(|e| *e == entry)
+            .map(|i| i + 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 按分数从高到低排序，分数相同时次数少、用时少的排前面
+    pub fn top(&self, filter: &ScoreFilter, n: usize) -> Vec<&ScoreEntry> {
+        let mut entries: Vec<&ScoreEntry> = self.entries.iter().filter(|e| filter.matches(e)).collect();
+        entries.sort_by_key(|e| (std::cmp::Reverse(e.score().total), e.attempts, e.elapsed_ms));
+        entries.truncate(n);
+        entries
+    }
+
+    // 表中出现过的难度，按第一次出现的顺序
+    pub fn difficulties(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !names.contains(&entry.difficulty.as_str()) {
+                names.push(&entry.difficulty);
+            }
+        }
+        names
+    }
+
+    // 删除匹配过滤条件的记录，返回删除的条数
+    pub fn reset(&mut self, filter: &ScoreFilter) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|e| !filter.matches(e));
+        before - self.entries.len()
+    }
+}
+
+#[derive(Debug)]
+pub enum ScoreError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Version(u32),
+}
+
+impl From<io::Error> for ScoreError {
+    fn from(e: io::Error) -> ScoreError {
+        ScoreError::Io(e)
+    }
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreError::Io(e) => write!(f, "cannot access score file: {}", e),
+            ScoreError::Parse(e) => write!(f, "score file is malformed: {}", e),
+            ScoreError::Version(v) => write!(f, "unsupported score file version {}", v),
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
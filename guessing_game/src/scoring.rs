@@ -0,0 +1,47 @@
+use std::fmt;
+use std::time::Duration;
+
+// 分数公式: 1000 - 50 x 猜测次数 - 2 x 秒数 - 提示的花费，最低为0，输掉的游戏没有分数
+pub const BASE_POINTS: u32 = 1000;
+pub const ATTEMPT_COST: u32 = 50;
+pub const SECOND_COST: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub attempts: u32,
+    pub seconds: u32,
+    pub hint_penalty: u32,
+    pub total: u32,
+}
+
+impl Score {
+    pub fn new(attempts: u32, elapsed: Duration, hint_penalty: u32) -> Score {
+        let seconds = elapsed.as_secs().min(u64::from(u32::MAX)) as u32;
+        let penalty = u64::from(ATTEMPT_COST) * u64::from(attempts)
+            + u64::from(SECOND_COST) * u64::from(seconds)
+            + u64::from(hint_penalty);
+        Score {
+            attempts,
+            seconds,
+            hint_penalty,
+            total: u64::from(BASE_POINTS).saturating_sub(penalty) as u32,
+        }
+    }
+}
+
+// 展示完整的计算过程，比如 1000 - 50 x 7 attempts - 2 x 12s - 75 hints = 551
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} - {} x {} attempts - {} x {}s - {} hints = {}",
+            BASE_POINTS,
+            ATTEMPT_COST,
+            self.attempts,
+            SECOND_COST,
+            self.seconds,
+            self.hint_penalty,
+            self.total
+        )
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{Datelike, NaiveDate};
+use rand::{Rng, SeedableRng, StdRng};
+
+// 秘密数字的来源，游戏本身不关心数字是随机的、可复现的还是固定的
+pub trait SecretSource {
+    // 返回闭区间[low, high]内的一个数字
+    fn next_secret(&mut self, low: u32, high: u32) -> u32;
+}
+
+// gen_range是左闭右开区间，上界要加一，转换成u64避免high为u32::MAX时溢出
+fn gen_inclusive<R: Rng>(rng: &mut R, low: u32, high: u32) -> u32 {
+    rng.gen_range(u64::from(low), u64::from(high) + 1) as u32
+}
+
+// 每次都使用线程本地的随机数生成器，无法复现
+pub struct RandomSecret;
+
+impl SecretSource for RandomSecret {
+    fn next_secret(&mut self, low: u32, high: u32) -> u32 {
+        gen_inclusive(&mut rand::thread_rng(), low, high)
+    }
+}
+
+// 使用种子初始化的伪随机数生成器，同样的种子总是得到同样的数字序列
+// 用于回归测试和比赛回放
+pub struct SeededSecret {
+    rng: StdRng,
+}
+
+impl SeededSecret {
+    pub fn new(seed: u64) -> SeededSecret {
+        // StdRng的种子是usize切片，拆成高低两半保证32位平台上也能用上全部的位
+        let key = [(seed & 0xffff_ffff) as usize, (seed >> 32) as usize];
+        SeededSecret {
+            rng: StdRng::from_seed(&key[..]),
+        }
+    }
+}
+
+impl SecretSource for SeededSecret {
+    fn next_secret(&mut self, low: u32, high: u32) -> u32 {
+        gen_inclusive(&mut self.rng, low, high)
+    }
+}
+
+// 固定的秘密数字，超出范围时会被截断到范围内
+pub struct FixedSecret(pub u32);
+
+impl SecretSource for FixedSecret {
+    fn next_secret(&mut self, low: u32, high: u32) -> u32 {
+        self.0.max(low).min(high)
+    }
+}
+
+// 每日挑战的秘密数字，只由日期和范围决定，同一天所有人拿到的数字都一样
+// 不使用StdRng，因为它在32位和64位平台上的实现不同，这里用splitmix64保证到处一致
+pub struct DailySecret {
+    seed: u64,
+}
+
+impl DailySecret {
+    pub fn new(date: NaiveDate) -> DailySecret {
+        let ymd =
+            date.year() as u64 * 10_000 + u64::from(date.month()) * 100 + u64::from(date.day());
+        DailySecret { seed: ymd }
+    }
+}
+
+impl SecretSource for DailySecret {
+    fn next_secret(&mut self, low: u32, high: u32) -> u32 {
+        self.seed = self.seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        let span = u64::from(high - low) + 1;
+        low + (z % span) as u32
+    }
+}
@@ -0,0 +1,424 @@
+// 多人联机的猜数字服务，基于TCP和按行的文本协议，每个连接一个线程
+//
+// 协议说明，每条消息都是一行，以空格分隔关键字和参数:
+//
+// 服务端 -> 客户端
+//   HELLO <id> <name>                连接成功，分配的玩家编号和默认名字
+//   ROUND <round> <low> <high>       新一轮开始，秘密数字在闭区间[low, high]内
+//   JOIN <name> / LEAVE <name>       有玩家加入或者离开
+//   RENAME <old> <new>               有玩家改名
+//   RESULT <name> <guess> too small  广播某个玩家的猜测结果，too small或too big
+//   RESULT <name> <guess> too big
+//   WINNER <name> <secret> <attempts>  广播本轮的获胜者，随后开始下一轮
+//   NOBODY <secret>                  所有玩家都用完了次数，本轮没有获胜者
+//   ERROR <message>                  只发给当前玩家的错误提示，比如输入不是数字
+//   BYE <reason>                     服务端即将关闭连接，比如长时间没有输入
+//
+// 客户端 -> 服务端
+//   <number> 或 GUESS <number>       猜一个数字
+//   NAME <name>                      修改自己的名字，名字中不能有空白
+//   QUIT                             断开连接
+//
+// 超过idle_timeout没有收到任何输入的玩家会收到 BYE idle timeout 后被断开
+// 一行超过MAX_LINE字节的玩家会收到 BYE line too long 后被断开，不会无限制地占用内存
+//
+// 发给玩家的消息先放进这个玩家的发送队列，由它自己的写线程发送，持有锁时不会阻塞在网络上
+// 客户端一直不读取导致队列写满，或者写超过write_timeout还没有完成时，服务端断开这个玩家
+use crate::config::GameConfig;
+use crate::game::{Feedback, GameState, GuessingGame};
+use crate::input::parse_guess;
+use crate::secret::SecretSource;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    pub game: GameConfig,
+    // 玩家超过这个时间没有输入就会被断开
+    pub idle_timeout: Duration,
+    // 发送一行最多等待的时间，超时说明客户端不再读取，断开这个玩家
+    pub write_timeout: Duration,
+}
+
+// 每个玩家的发送队列最多积压的消息数
+const OUTBOX_LEN: usize = 256;
+
+// 客户端发来的一行最多这么多字节，不包括换行
+pub const MAX_LINE: usize = 1024;
+
+// accept出错后等一会儿再继续，比如文件描述符用完时不会空转
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            game: GameConfig::default(),
+            idle_timeout: Duration::from_secs(300),
+            write_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+// 一个在线的玩家，每个玩家在本轮有自己的猜测次数，但秘密数字是共享的
+struct Player {
+    name: String,
+    outbox: SyncSender<String>,
+    // 只用来断开连接，读写分别在读线程和写线程里
+    stream: TcpStream,
+    game: GuessingGame,
+}
+
+impl Player {
+    // 队列满了就断开连接，读线程会因此退出并调用leave
+    fn send(&self, msg: &str) {
+        if self.outbox.try_send(msg.to_string()).is_err() {
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// 所有连接线程共享的状态，通过Mutex保护
+struct Shared {
+    config: ServerConfig,
+    source: Box<dyn SecretSource + Send>,
+    round: u32,
+    secret: u32,
+    next_id: u32,
+    players: BTreeMap<u32, Player>,
+    // 服务已经停止，停止前接受但还没有加入的连接加入后马上断开
+    stopped: bool,
+}
+
+impl Shared {
+    // 发给所有玩家，发送失败的连接由它自己的读线程负责清理
+    fn broadcast(&mut self, msg: &str) {
+        for player in self.players.values() {
+            player.send(msg);
+        }
+    }
+
+    fn send(&mut self, id: u32, msg: &str) {
+        if let Some(player) = self.players.get(&id) {
+            player.send(msg);
+        }
+    }
+
+    // 生成新的秘密数字，所有玩家的次数清零
+    fn start_round(&mut self) {
+        let game = self.config.game;
+        self.round += 1;
+        self.secret = self.source.next_secret(game.low, game.high);
+        for player in self.players.values_mut() {
+            player.game = GuessingGame::with_config(game, self.secret);
+        }
+        let msg = format!("ROUND {} {} {}", self.round, game.low, game.high);
+        self.broadcast(&msg);
+    }
+
+    fn join(&mut self, outbox: SyncSender<String>, stream: TcpStream) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let name = format!("player{}", id);
+        let game = self.config.game;
+
+        self.broadcast(&format!("JOIN {}", name));
+        self.players.insert(
+            id,
+            Player {
+                name: name.clone(),
+                outbox,
+                stream,
+                game: GuessingGame::with_config(game, self.secret),
+            },
+        );
+        self.send(id, &format!("HELLO {} {}", id, name));
+        self.send(
+            id,
+            &format!("ROUND {} {} {}", self.round, game.low, game.high),
+        );
+        if self.stopped {
+            self.disconnect_all("server stopped");
+        }
+        id
+    }
+
+    fn leave(&mut self, id: u32, reason: &str) {
+        // 丢掉player之后写线程发完BYE就会结束
+        if let Some(player) = self.players.remove(&id) {
+            player.send(&format!("BYE {}", reason));
+            self.broadcast(&format!("LEAVE {}", player.name));
+            // 离开的可能是本轮最后一个还有次数的玩家
+            self.check_round_over();
+        }
+    }
+
+    // 服务停止时断开所有玩家，关闭读的一端让读线程退出，之后它调用leave时玩家已经不在了
+    fn disconnect_all(&mut self, reason: &str) {
+        self.stopped = true;
+        for player in std::mem::take(&mut self.players).into_values() {
+            player.send(&format!("BYE {}", reason));
+            let _ = player.stream.shutdown(Shutdown::Read);
+        }
+    }
+
+    // 在线的玩家都用完了次数时，没有人能猜中也没有人能再猜，公布答案开始下一轮
+    // 所有玩家都离开时也直接开始新的一轮，之后加入的玩家不会接着猜一个已经被猜过很多次的数字
+    fn check_round_over(&mut self) {
+        if self.players.is_empty() {
+            self.start_round();
+        } else if self
+            .players
+            .values()
+            .all(|p| p.game.state() == GameState::Lost)
+        {
+            let msg = format!("NOBODY {}", self.secret);
+            self.broadcast(&msg);
+            self.start_round();
+        }
+    }
+
+    fn rename(&mut self, id: u32, name: &str) {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            self.send(id, "ERROR name must be a single word");
+            return;
+        }
+        if self.players.values().any(|p| p.name == name) {
+            self.send(id, &format!("ERROR name {} is already taken", name));
+            return;
+        }
+        if let Some(player) = self.players.get_mut(&id) {
+            let old = std::mem::replace(&mut player.name, name.to_string());
+            self.broadcast(&format!("RENAME {} {}", old, name));
+        }
+    }
+
+    fn guess(&mut self, id: u32, input: &str) {
+        let guess = match parse_guess(input, &self.config.game) {
+            Ok(guess) => guess,
+            Err(e) => return self.send(id, &format!("ERROR {}", e)),
+        };
+        let player = match self.players.get_mut(&id) {
+            Some(player) => player,
+            None => return,
+        };
+        let feedback = player.game.guess(guess);
+        let name = player.name.clone();
+        let attempts = player.game.attempts();
+        let out_of_attempts = player.game.state() == GameState::Lost;
+
+        match feedback {
+            Feedback::TooSmall => self.broadcast(&format!("RESULT {} {} too small", name, guess)),
+            Feedback::TooBig => self.broadcast(&format!("RESULT {} {} too big", name, guess)),
+            Feedback::Correct => {
+                let msg = format!("WINNER {} {} {}", name, self.secret, attempts);
+                self.broadcast(&msg);
+                return self.start_round();
+            }
+            Feedback::OutOfRange { .. } => unreachable!("parse_guess checks the range"),
+            Feedback::GameOver => {
+                return self.send(id, "ERROR no attempts left, wait for the next round")
+            }
+        }
+
+        if out_of_attempts {
+            self.send(id, "ERROR no attempts left, wait for the next round");
+            self.check_round_over();
+        }
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>,
+    stopping: Arc<AtomicBool>,
+}
+
+impl Server {
+    // 绑定端口并生成第一轮的秘密数字，端口为0时由系统分配，方便测试
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        config: ServerConfig,
+        source: Box<dyn SecretSource + Send>,
+    ) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let mut shared = Shared {
+            config,
+            source,
+            round: 0,
+            secret: 0,
+            next_id: 1,
+            players: BTreeMap::new(),
+            stopped: false,
+        };
+        shared.start_round();
+        Ok(Server {
+            listener,
+            shared: Arc::new(Mutex::new(shared)),
+            stopping: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // 一直接受新的连接，每个连接交给单独的线程处理，通过ServerHandle停止后断开所有玩家并返回
+    // 接受某个连接失败不影响服务，记录下来继续等下一个
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            if self.stopping.load(Ordering::SeqCst) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("accept failed: {}", e);
+                    thread::sleep(ACCEPT_BACKOFF);
+                    continue;
+                }
+            };
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || {
+                let _ = handle_client(stream, shared);
+            });
+        }
+        self.shared.lock().unwrap().disconnect_all("server stopped");
+        Ok(())
+    }
+
+    // 在后台线程里运行，返回的ServerHandle可以用来停止服务
+    pub fn spawn(self) -> io::Result<ServerHandle> {
+        let addr = self.local_addr()?;
+        let stopping = Arc::clone(&self.stopping);
+        let thread = thread::spawn(move || self.run());
+        Ok(ServerHandle {
+            addr,
+            stopping,
+            thread: Some(thread),
+        })
+    }
+}
+
+// 后台运行的服务，调用stop或者drop时停止
+pub struct ServerHandle {
+    addr: SocketAddr,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl ServerHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // 通知服务停止并等待它断开所有玩家，返回run的结果
+    pub fn stop(mut self) -> io::Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let thread = match self.thread.take() {
+            Some(thread) => thread,
+            None => return Ok(()),
+        };
+        self.stopping.store(true, Ordering::SeqCst);
+        // accept会一直阻塞，自己连一次把它唤醒；绑定在0.0.0.0上时连本机的回环地址
+        let mut wake = self.addr;
+        if wake.ip().is_unspecified() {
+            wake.set_ip(match wake.ip() {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            });
+        }
+        let _ = TcpStream::connect(wake);
+        thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("server thread panicked")))
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn handle_client(stream: TcpStream, shared: Arc<Mutex<Shared>>) -> io::Result<()> {
+    let config = shared.lock().unwrap().config;
+    stream.set_read_timeout(Some(config.idle_timeout))?;
+    stream.set_write_timeout(Some(config.write_timeout))?;
+    let (outbox, queued) = mpsc::sync_channel(OUTBOX_LEN);
+    let writer = stream.try_clone()?;
+    thread::spawn(move || write_queued(writer, queued));
+    let id = shared.lock().unwrap().join(outbox, stream.try_clone()?);
+
+    let mut reader = BufReader::new(stream);
+    let reason = loop {
+        // 最多读MAX_LINE加上换行的字节数，读满了还没有换行说明这一行太长
+        let mut line = Vec::new();
+        match (&mut reader)
+            .take(MAX_LINE as u64 + 1)
+            .read_until(b'\n', &mut line)
+        {
+            Ok(0) => break "connection closed",
+            Ok(_) => {}
+            // 读超时在不同平台上分别是WouldBlock或者TimedOut
+            Err(ref e)
+                if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
+            {
+                break "idle timeout"
+            }
+            Err(_) => break "read error",
+        }
+        if line.len() > MAX_LINE && !line.ends_with(b"\n") {
+            break "line too long";
+        }
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(_) => break "read error",
+        };
+
+        let line = line.trim();
+        let mut shared = shared.lock().unwrap();
+        if line.eq_ignore_ascii_case("QUIT") {
+            break "quit";
+        } else if let Some(name) = strip_keyword(line, "NAME") {
+            shared.rename(id, name);
+        } else {
+            shared.guess(id, strip_keyword(line, "GUESS").unwrap_or(line));
+        }
+    };
+
+    shared.lock().unwrap().leave(id, reason);
+    Ok(())
+}
+
+// 写线程：按顺序发送队列里的消息，队列的发送端被丢掉（玩家离开）后结束
+// 写失败或者超时就断开连接，让读线程去做清理
+fn write_queued(mut stream: TcpStream, queued: Receiver<String>) {
+    for msg in queued {
+        let line = msg + "\n";
+        if stream.write_all(line.as_bytes()).is_err() {
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+// 去掉不区分大小写的关键字前缀，返回后面的参数
+fn strip_keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let (head, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+    if head.eq_ignore_ascii_case(keyword) {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,148 @@
+use crate::game::{Feedback, GameState, GuessingGame};
+use crate::secret::{SecretSource, SeededSecret};
+use std::cmp::Ordering;
+
+// 电脑玩家的策略，根据上一次猜测的反馈给出下一次猜测
+pub trait Solver {
+    fn name(&self) -> &'static str;
+
+    // 开始新的一局，范围是闭区间[low, high]
+    fn reset(&mut self, low: u32, high: u32);
+
+    // last是上一次猜测相对于秘密数字的大小，第一次猜测时为None
+    fn next_guess(&mut self, last: Option<Ordering>) -> u32;
+}
+
+// 所有内置策略的名称，用于命令行选择
+pub const SOLVERS: [&str; 3] = ["binary", "random", "linear"];
+
+// 按名称创建策略，random策略使用给定的种子保证可以复现
+// 种子会先打乱一下，否则和同种子的秘密数字序列完全一样，第一次就能猜中
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Solver>> {
+    match name {
+        "binary" => Some(Box::new(BinarySearch::default())),
+        "random" => Some(Box::new(RandomGuess::new(seed ^ 0x9e37_79b9_7f4a_7c15))),
+        "linear" => Some(Box::new(Linear::default())),
+        _ => None,
+    }
+}
+
+// 二分查找，每次猜剩余范围的中点，最优策略
+#[derive(Debug, Default)]
+pub struct BinarySearch {
+    low: u32,
+    high: u32,
+    last: u32,
+}
+
+impl Solver for BinarySearch {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn reset(&mut self, low: u32, high: u32) {
+        self.low = low;
+        self.high = high;
+    }
+
+    fn next_guess(&mut self, last: Option<Ordering>) -> u32 {
+        narrow(&mut self.low, &mut self.high, self.last, last);
+        self.last = self.low + (self.high - self.low) / 2;
+        self.last
+    }
+}
+
+// 在剩余范围内随机猜一个数
+pub struct RandomGuess {
+    source: SeededSecret,
+    low: u32,
+    high: u32,
+    last: u32,
+}
+
+impl RandomGuess {
+    pub fn new(seed: u64) -> RandomGuess {
+        RandomGuess {
+            source: SeededSecret::new(seed),
+            low: 0,
+            high: 0,
+            last: 0,
+        }
+    }
+}
+
+impl Solver for RandomGuess {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn reset(&mut self, low: u32, high: u32) {
+        self.low = low;
+        self.high = high;
+    }
+
+    fn next_guess(&mut self, last: Option<Ordering>) -> u32 {
+        narrow(&mut self.low, &mut self.high, self.last, last);
+        self.last = self.source.next_secret(self.low, self.high);
+        self.last
+    }
+}
+
+// 故意写得很差的策略，从下界开始一个一个往上猜，完全不看反馈
+#[derive(Debug, Default)]
+pub struct Linear {
+    next: u32,
+}
+
+impl Solver for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn reset(&mut self, low: u32, _high: u32) {
+        self.next = low;
+    }
+
+    fn next_guess(&mut self, _last: Option<Ordering>) -> u32 {
+        let guess = self.next;
+        self.next = self.next.saturating_add(1);
+        guess
+    }
+}
+
+// 根据上一次的反馈缩小剩余范围
+fn narrow(low: &mut u32, high: &mut u32, guess: u32, last: Option<Ordering>) {
+    match last {
+        Some(Ordering::Less) => *low = guess + 1,
+        Some(Ordering::Greater) => *high = guess - 1,
+        _ => {}
+    }
+}
+
+// 让策略玩完一局，每次猜测后调用on_guess，可以用来输出过程
+pub fn solve<S, F>(game: &mut GuessingGame, solver: &mut S, mut on_guess: F) -> GameState
+where
+    S: Solver + ?Sized,
+    F: FnMut(u32, Feedback),
+{
+    let config = *game.config();
+    solver.reset(config.low, config.high);
+
+    let mut last = None;
+    while game.state() == GameState::Playing {
+        let guess = solver.next_guess(last);
+        let feedback = game.guess(guess);
+        on_guess(guess, feedback);
+        last = match feedback {
+            Feedback::TooSmall => Some(Ordering::Less),
+            Feedback::TooBig => Some(Ordering::Greater),
+            Feedback::Correct => Some(Ordering::Equal),
+            // 策略给出了范围外的数字，放弃这一局避免死循环
+            Feedback::OutOfRange { .. } | Feedback::GameOver => {
+                game.give_up();
+                None
+            }
+        };
+    }
+    game.state()
+}
@@ -0,0 +1,212 @@
+use crate::config::GameConfig;
+use crate::frontend::{play, Frontend, ScriptedFrontend};
+use crate::game::GuessingGame;
+use crate::i18n::Lang;
+use crate::input::parse_guess;
+use crate::secret::SeededSecret;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+const TRANSCRIPT_VERSION: u32 = 1;
+
+// 一局游戏的完整记录，同样的种子和配置再喂一遍同样的输入，应该得到完全一样的输出
+// 既可以用来复现玩家报告的问题，也可以作为游戏循环的golden file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub seed: u64,
+    pub config: GameConfig,
+    // 记录时使用的语言，回放时要用同样的语言才能得到同样的输出
+    #[serde(default)]
+    pub lang: Lang,
+    // RFC 3339格式的开始时间
+    pub started: String,
+    // 第一次读取输入之前的输出，比如欢迎信息
+    pub intro: Vec<String>,
+    pub turns: Vec<Turn>,
+}
+
+// 玩家的一行输入，以及这行输入之后游戏给出的所有输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Turn {
+    // 距离游戏开始的毫秒数
+    pub at_ms: u64,
+    // 原始输入，包括换行符
+    pub input: String,
+    // 作为猜测解析出来的数字，提示请求或者非法输入为None
+    pub parsed: Option<u32>,
+    pub output: Vec<String>,
+}
+
+impl Transcript {
+    pub fn new(seed: u64, config: GameConfig, started: String) -> Transcript {
+        Transcript {
+            version: TRANSCRIPT_VERSION,
+            seed,
+            config,
+            lang: Lang::default(),
+            started,
+            intro: Vec::new(),
+            turns: Vec::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Transcript, TranscriptError> {
+        let content = fs::read_to_string(path)?;
+        let transcript: Transcript =
+            serde_json::from_str(&content).map_err(TranscriptError::Parse)?;
+        if transcript.version != TRANSCRIPT_VERSION {
+            return Err(TranscriptError::Version(transcript.version));
+        }
+        Ok(transcript)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TranscriptError> {
+        let content = serde_json::to_string_pretty(self).map_err(TranscriptError::Parse)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.turns.iter().map(|t| t.input.as_str())
+    }
+}
+
+// 包装另一个前端，把经过的输入输出都记录下来
+pub struct RecordingFrontend<F> {
+    inner: F,
+    started: Instant,
+    transcript: Transcript,
+}
+
+impl<F: Frontend> RecordingFrontend<F> {
+    pub fn new(inner: F, mut transcript: Transcript) -> RecordingFrontend<F> {
+        transcript.lang = inner.lang();
+        RecordingFrontend {
+            inner,
+            started: Instant::now(),
+            transcript,
+        }
+    }
+
+    pub fn into_transcript(self) -> Transcript {
+        self.transcript
+    }
+}
+
+impl<F: Frontend> Frontend for RecordingFrontend<F> {
+    fn read_input(&mut self) -> io::Result<Option<String>> {
+        let input = self.inner.read_input()?;
+        if let Some(ref line) = input {
+            self.transcript.turns.push(Turn {
+                at_ms: self.started.elapsed().as_millis() as u64,
+                input: line.clone(),
+                parsed: parse_guess(line, &self.transcript.config).ok(),
+                output: Vec::new(),
+            });
+        }
+        Ok(input)
+    }
+
+    fn show(&mut self, msg: &str) -> io::Result<()> {
+        match self.transcript.turns.last_mut() {
+            Some(turn) => turn.output.push(msg.to_string()),
+            None => self.transcript.intro.push(msg.to_string()),
+        }
+        self.inner.show(msg)
+    }
+
+    fn lang(&self) -> Lang {
+        self.inner.lang()
+    }
+}
+
+// 用记录里的种子、配置和输入重新玩一遍，返回新的记录
+pub fn rerun(transcript: &Transcript) -> io::Result<Transcript> {
+    let inputs = transcript.inputs().map(String::from).collect::<Vec<_>>();
+    let fresh = Transcript::new(
+        transcript.seed,
+        transcript.config,
+        transcript.started.clone(),
+    );
+    let mut frontend = RecordingFrontend::new(ScriptedFrontend::new(inputs).with_lang(transcript.lang), fresh);
+
+    let mut source = SeededSecret::new(transcript.seed);
+    let mut game = GuessingGame::from_source(transcript.config, &mut source);
+    play(&mut game, &mut frontend)?;
+    Ok(frontend.into_transcript())
+}
+
+// 回放并逐条比较输出，返回回放的轮数，第一处不一致时返回错误
+pub fn verify(transcript: &Transcript) -> Result<usize, TranscriptError> {
+    let actual = rerun(transcript)?;
+    if actual.intro != transcript.intro {
+        return Err(TranscriptError::Mismatch {
+            turn: 0,
+            expected: transcript.intro.join(" | "),
+            actual: actual.intro.join(" | "),
+        });
+    }
+    for (i, (expected, actual)) in transcript.turns.iter().zip(&actual.turns).enumerate() {
+        if expected.parsed != actual.parsed || expected.output != actual.output {
+            return Err(TranscriptError::Mismatch {
+                turn: i + 1,
+                expected: expected.output.join(" | "),
+                actual: actual.output.join(" | "),
+            });
+        }
+    }
+    // 游戏提前结束时后面的输入不会被读取
+    if actual.turns.len() != transcript.turns.len() {
+        return Err(TranscriptError::Mismatch {
+            turn: actual.turns.len().min(transcript.turns.len()) + 1,
+            expected: format!("{} turns", transcript.turns.len()),
+            actual: format!("{} turns", actual.turns.len()),
+        });
+    }
+    Ok(actual.turns.len())
+}
+
+#[derive(Debug)]
+pub enum TranscriptError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Version(u32),
+    // turn为0表示第一次输入之前的输出不一致
+    Mismatch {
+        turn: usize,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl From<io::Error> for TranscriptError {
+    fn from(e: io::Error) -> TranscriptError {
+        TranscriptError::Io(e)
+    }
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranscriptError::Io(e) => write!(f, "cannot access transcript: {}", e),
+            TranscriptError::Parse(e) => write!(f, "transcript is malformed: {}", e),
+            TranscriptError::Version(v) => write!(f, "unsupported transcript version {}", v),
+            TranscriptError::Mismatch {
+                turn,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "turn {}: expected [{}] but got [{}]",
+                turn, expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
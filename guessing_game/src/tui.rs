@@ -0,0 +1,225 @@
+// 全屏的终端界面，和行模式用的是同一个GuessingGame，只是换了一种展示方式
+// 上面是剩余范围的条形图，随着猜测逐渐变窄，下面是次数、计时和猜测历史
+use crate::game::{Feedback, GameState, GuessingGame};
+use crate::hint::{is_hint_request, parse_hint};
+use crate::i18n::{Lang, Msg};
+use crate::input::parse_guess;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
+use ratatui::Frame;
+use std::io;
+use std::time::{Duration, Instant};
+
+// 界面需要的状态，游戏本身的状态都在GuessingGame里
+struct App {
+    input: String,
+    message: String,
+    // 每次输入和对应的结果，最新的在最后
+    log: Vec<(String, Color)>,
+    started: Instant,
+    finished: Option<Duration>,
+    // 限时模式的总时间
+    limit: Option<Duration>,
+    lang: Lang,
+}
+
+impl App {
+    fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.started.elapsed())
+    }
+}
+
+// 运行全屏界面直到游戏结束，返回游戏的最终状态
+// Esc或者Ctrl-C视为放弃，游戏结束后按任意键退出
+// 指定了限时的话时间一到就判负，不用等玩家按键
+pub fn run(game: &mut GuessingGame, lang: Lang, limit: Option<Duration>) -> io::Result<GameState> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, game, lang, limit);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    game: &mut GuessingGame,
+    lang: Lang,
+    limit: Option<Duration>,
+) -> io::Result<GameState> {
+    let mut app = App {
+        input: String::new(),
+        message: lang.text(Msg::TuiHelp),
+        log: Vec::new(),
+        started: Instant::now(),
+        finished: None,
+        limit,
+        lang,
+    };
+
+    loop {
+        if game.state() == GameState::Playing
+            && limit.is_some_and(|limit| app.started.elapsed() >= limit)
+        {
+            game.give_up();
+            app.finished = limit;
+            app.message = match game.secret() {
+                Some(secret) => format!(
+                    "{} {}",
+                    lang.text(Msg::TimeUp),
+                    lang.text(Msg::TuiLost { secret })
+                ),
+                None => lang.text(Msg::TimeUp),
+            };
+        }
+        terminal.draw(|frame| draw(frame, game, &app))?;
+
+        // 定时刷新界面让计时器走动
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        if game.state() != GameState::Playing {
+            return Ok(game.state());
+        }
+
+        match key.code {
+            KeyCode::Esc => game.give_up(),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => game.give_up(),
+            KeyCode::Char(c) => app.input.push(c),
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Enter => submit(game, &mut app),
+            _ => {}
+        }
+
+        if game.state() != GameState::Playing && app.finished.is_none() {
+            app.finished = Some(app.started.elapsed());
+            app.message = match (game.state(), game.secret()) {
+                (GameState::Won, _) => lang.text(Msg::TuiWon),
+                (_, Some(secret)) => lang.text(Msg::TuiLost { secret }),
+                _ => String::new(),
+            };
+        }
+    }
+}
+
+// 处理一行输入，和行模式一样支持猜测和提示
+fn submit(game: &mut GuessingGame, app: &mut App) {
+    let input = std::mem::take(&mut app.input);
+    let input = input.trim();
+
+    if is_hint_request(input) {
+        app.message = match parse_hint(&input[4..]) {
+            Some(hint) => match game.hint(hint) {
+                Some(result) => app.lang.text(Msg::HintUsed(hint, result)),
+                None => app.lang.text(Msg::NoMoreHints),
+            },
+            None => app.lang.text(Msg::HintHelp),
+        };
+        return;
+    }
+
+    let guess = match parse_guess(input, game.config()) {
+        Ok(guess) => guess,
+        Err(e) => {
+            app.message = app.lang.text(Msg::Input(&e));
+            return;
+        }
+    };
+    let feedback = game.guess(guess);
+    let color = match feedback {
+        Feedback::TooSmall => Color::Cyan,
+        Feedback::TooBig => Color::Magenta,
+        Feedback::Correct => Color::Green,
+        Feedback::OutOfRange { .. } | Feedback::GameOver => return,
+    };
+    let text = app.lang.text(Msg::Feedback(feedback));
+    app.log.push((format!("{:>10}  {}", guess, text), color));
+    app.message.clear();
+}
+
+fn draw(frame: &mut Frame, game: &GuessingGame, app: &App) {
+    let [title, bar, stats, history, input, message] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let lang = app.lang;
+    frame.render_widget(Paragraph::new(lang.text(Msg::Welcome).bold()), title);
+    draw_range_bar(frame, game, bar);
+
+    frame.render_widget(
+        Paragraph::new(lang.text(Msg::TuiStats {
+            attempts: game.attempts(),
+            remaining: game.remaining(),
+            seconds: app.elapsed().as_secs(),
+            limit: app.limit.map(|limit| limit.as_secs()),
+            hint_penalty: game.hint_penalty(),
+        })),
+        stats,
+    );
+
+    // 历史记录只显示放得下的最新几条
+    let visible = history.height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = app
+        .log
+        .iter()
+        .skip(app.log.len().saturating_sub(visible))
+        .map(|(text, color)| ListItem::new(text.as_str()).style(Style::default().fg(*color)))
+        .collect();
+    frame.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(lang.text(Msg::TuiHistory)),
+        ),
+        history,
+    );
+
+    frame.render_widget(
+        Paragraph::new(app.input.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(lang.text(Msg::TuiYourGuess)),
+        ),
+        input,
+    );
+    frame.render_widget(Paragraph::new(app.message.as_str().yellow()), message);
+}
+
+// 整个配置范围画成一条横线，秘密数字可能所在的部分高亮显示
+fn draw_range_bar(frame: &mut Frame, game: &GuessingGame, area: Rect) {
+    let config = game.config();
+    let (low, high) = game.bounds();
+    let width = u64::from(area.width.saturating_sub(2)).max(1);
+    let total = u64::from(config.high - config.low) + 1;
+
+    // 把数值映射到列，保证剩余范围至少占一列
+    let column = |value: u32| u64::from(value - config.low) * width / total;
+    let start = column(low);
+    let end = (column(high) + 1).max(start + 1).min(width);
+
+    let bar: String = (0..width)
+        .map(|i| if i >= start && i < end { '█' } else { '░' })
+        .collect();
+    let line = Line::from(vec![Span::styled(bar, Style::default().fg(Color::Green))]);
+    frame.render_widget(
+        Paragraph::new(line).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} ..= {}", low, high)),
+        ),
+        area,
+    );
+}
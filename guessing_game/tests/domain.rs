@@ -0,0 +1,29 @@
+// 构造小数范围时检查三个值的精度
+use guessing_game::{Decimal, Decimals, Domain, DomainError};
+
+fn decimal(input: &str, precision: u32) -> Decimal {
+    Decimal::parse(input, precision).unwrap()
+}
+
+#[test]
+fn decimals_need_the_same_precision() {
+    let decimals = Decimals::new(decimal("0", 2), decimal("1", 2), decimal("0.05", 2)).unwrap();
+    assert_eq!(decimals.bounds(), (decimal("0.00", 2), decimal("1.00", 2)));
+
+    assert_eq!(
+        Decimals::new(decimal("0", 2), decimal("1", 3), decimal("0.05", 2)).unwrap_err(),
+        DomainError::MixedPrecision {
+            low: 2,
+            high: 3,
+            tolerance: 2,
+        }
+    );
+    assert_eq!(
+        Decimals::new(decimal("0", 1), decimal("1", 1), decimal("0.05", 2)).unwrap_err(),
+        DomainError::MixedPrecision {
+            low: 1,
+            high: 1,
+            tolerance: 2,
+        }
+    );
+}
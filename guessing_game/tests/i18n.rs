@@ -0,0 +1,332 @@
+// 每条消息在每种语言里都要有自己的翻译，不能是空的，也不能直接照搬英文
+use guessing_game::domain::{DomainError, DomainKind};
+use guessing_game::game::Feedback;
+use guessing_game::hint::{Hint, HintResult};
+use guessing_game::input::InputError;
+use guessing_game::mastermind::Marks;
+use guessing_game::reverse::{Answer, Contradiction};
+use guessing_game::scoring::Score;
+use guessing_game::{Lang, Msg};
+use std::cmp::Ordering;
+
+// 新增Msg的变体后这里无法通过编译，提醒在samples里加上对应的消息
+fn covered(msg: Msg) {
+    match msg {
+        Msg::Welcome
+        | Msg::Range { .. }
+        | Msg::RangeWithAttempts { .. }
+        | Msg::HintIntro
+        | Msg::Prompt
+        | Msg::YouGuessed(_)
+        | Msg::Feedback(_)
+        | Msg::Input(_)
+        | Msg::HintHelp
+        | Msg::HintText(_)
+        | Msg::HintUsed(..)
+        | Msg::NoMoreHints
+        | Msg::Won { .. }
+        | Msg::Lost { .. }
+        | Msg::Unfinished { .. }
+        | Msg::SolverGuess { .. }
+        | Msg::Score(_)
+        | Msg::HighScore { .. }
+        | Msg::ReverseIntro { .. }
+        | Msg::ReverseAsk(_)
+        | Msg::ReverseBadAnswer
+        | Msg::ReverseGotIt { .. }
+        | Msg::ReverseGiveUp
+        | Msg::Answer(_)
+        | Msg::Contradiction(_)
+        | Msg::TuiHelp
+        | Msg::TuiWon
+        | Msg::TuiLost { .. }
+        | Msg::TuiStats { .. }
+        | Msg::TimeLimit { .. }
+        | Msg::TimeLeft { .. }
+        | Msg::TimeUp
+        | Msg::Daily { .. }
+        | Msg::TuiHistory
+        | Msg::TuiYourGuess
+        | Msg::DomainRange { .. }
+        | Msg::Tolerance(_)
+        | Msg::DomainFeedback { .. }
+        | Msg::Domain(_)
+        | Msg::DomainWon { .. }
+        | Msg::DomainLost { .. }
+        | Msg::MastermindIntro { .. }
+        | Msg::Marks(_)
+        | Msg::MastermindSolverGuess { .. }
+        | Msg::KnuthReport { .. }
+        | Msg::KnuthBound { .. }
+        | Msg::ScoreNotSaved(_)
+        | Msg::NoScores
+        | Msg::ScoresRemoved(_)
+        | Msg::ScoresHeading(_)
+        | Msg::ScoreRow { .. }
+        | Msg::BenchHeader { .. }
+        | Msg::BenchStrategy { .. }
+        | Msg::BenchBound { .. }
+        | Msg::Replayed { .. }
+        | Msg::Listening(_) => {}
+    }
+}
+
+#[test]
+fn every_message_is_translated() {
+    let feedbacks = [
+        Feedback::TooSmall,
+        Feedback::TooBig,
+        Feedback::Correct,
+        Feedback::OutOfRange { low: 1, high: 100 },
+        Feedback::GameOver,
+    ];
+    let input_errors = [
+        InputError::Empty,
+        InputError::NotANumber("abc".to_string()),
+        InputError::Negative("-1".to_string()),
+        InputError::Overflow("99999999999".to_string()),
+        InputError::OutOfRange {
+            value: 101,
+            low: 1,
+            high: 100,
+        },
+        InputError::Eof,
+    ];
+    let domain_errors = [
+        DomainError::Empty,
+        DomainError::Number(InputError::Empty),
+        DomainError::NotALetter("ab".to_string()),
+        DomainError::NotADecimal("x".to_string()),
+        DomainError::TooPrecise {
+            input: "1.234".to_string(),
+            precision: 2,
+        },
+        DomainError::UnknownWord("qwerty".to_string()),
+        DomainError::NotACode("12a4".to_string()),
+        DomainError::WrongLength {
+            input: "123".to_string(),
+            expected: 4,
+        },
+        DomainError::OutOfRange {
+            value: "z".to_string(),
+            low: "a".to_string(),
+            high: "m".to_string(),
+        },
+        DomainError::EmptyRange,
+        DomainError::RangeTooLarge,
+        DomainError::MixedPrecision {
+            low: 2,
+            high: 2,
+            tolerance: 3,
+        },
+        DomainError::Eof,
+    ];
+    let hints = [
+        HintResult::Parity { even: true },
+        HintResult::Parity { even: false },
+        HintResult::Divisible {
+            divisor: 3,
+            divisible: true,
+        },
+        HintResult::Divisible {
+            divisor: 3,
+            divisible: false,
+        },
+        HintResult::DigitSum(9),
+        HintResult::Temperature(Ordering::Less),
+        HintResult::Temperature(Ordering::Greater),
+        HintResult::Temperature(Ordering::Equal),
+        HintResult::NeedTwoGuesses,
+    ];
+    let kinds = [
+        DomainKind::Number,
+        DomainKind::Letter,
+        DomainKind::Decimal,
+        DomainKind::Word,
+    ];
+    let score = Score::new(3, std::time::Duration::from_secs(10), 50);
+    let answers: Vec<Answer> = [Ordering::Less, Ordering::Greater, Ordering::Equal]
+        .iter()
+        .enumerate()
+        .map(|(i, &ordering)| Answer {
+            turn: i as u32 + 1,
+            guess: 50,
+            ordering,
+        })
+        .collect();
+    let contradictions = [
+        Contradiction {
+            earlier: Some(answers[0]),
+            latest: answers[1],
+            low: 1,
+            high: 100,
+        },
+        Contradiction {
+            earlier: None,
+            latest: answers[1],
+            low: 1,
+            high: 100,
+        },
+    ];
+    let marks = Marks {
+        exact: 1,
+        misplaced: 2,
+    };
+
+    let mut samples = vec![
+        Msg::Welcome,
+        Msg::Range { low: 1, high: 100 },
+        Msg::RangeWithAttempts {
+            low: 1,
+            high: 100,
+            max: 7,
+        },
+        Msg::HintIntro,
+        Msg::Prompt,
+        Msg::YouGuessed("50"),
+        Msg::HintHelp,
+        Msg::HintUsed(Hint::Parity, hints[0]),
+        Msg::NoMoreHints,
+        Msg::Won { attempts: 3 },
+        Msg::Lost {
+            secret: 42,
+            attempts: 7,
+        },
+        Msg::Unfinished { attempts: 2 },
+        Msg::SolverGuess {
+            solver: "binary",
+            guess: 50,
+            feedback: Feedback::TooBig,
+        },
+        Msg::Score(&score),
+        Msg::HighScore {
+            rank: 1,
+            difficulty: "normal",
+        },
+        Msg::ReverseIntro { low: 1, high: 100 },
+        Msg::ReverseAsk(50),
+        Msg::ReverseBadAnswer,
+        Msg::ReverseGotIt { attempts: 5 },
+        Msg::ReverseGiveUp,
+        Msg::TuiHelp,
+        Msg::TuiWon,
+        Msg::TuiLost { secret: 42 },
+        Msg::TuiStats {
+            attempts: 2,
+            remaining: Some(5),
+            seconds: 75,
+            limit: Some(120),
+            hint_penalty: 50,
+        },
+        Msg::TuiStats {
+            attempts: 2,
+            remaining: None,
+            seconds: 75,
+            limit: None,
+            hint_penalty: 0,
+        },
+        Msg::TimeLimit { seconds: 60 },
+        Msg::TimeLeft { seconds: 10 },
+        Msg::TimeUp,
+        Msg::Daily { date: "2024-05-01" },
+        Msg::TuiHistory,
+        Msg::TuiYourGuess,
+        Msg::Tolerance("0.01"),
+        Msg::DomainWon { attempts: 3 },
+        Msg::DomainLost {
+            secret: "m",
+            attempts: 5,
+        },
+        Msg::MastermindIntro {
+            pegs: 4,
+            colours: 6,
+            max: Some(10),
+        },
+        Msg::MastermindIntro {
+            pegs: 4,
+            colours: 6,
+            max: None,
+        },
+        Msg::Marks(marks),
+        Msg::MastermindSolverGuess {
+            guess: "1122",
+            marks,
+            candidates: 256,
+        },
+        Msg::KnuthReport {
+            games: 1296,
+            pegs: 4,
+            colours: 6,
+            average: 4.476,
+            max: 5,
+        },
+        Msg::KnuthBound { ok: true },
+        Msg::KnuthBound { ok: false },
+        Msg::ScoreNotSaved("permission denied"),
+        Msg::NoScores,
+        Msg::ScoresRemoved(3),
+        Msg::ScoresHeading("normal"),
+        Msg::ScoreRow {
+            rank: 1,
+            player: "dot",
+            points: 850,
+            attempts: 3,
+            seconds: 12.5,
+            low: 1,
+            high: 100,
+            date: "2024-05-01 08:00",
+        },
+        Msg::BenchHeader {
+            games: 1000,
+            low: 1,
+            high: 100,
+            seed: 0,
+        },
+        Msg::BenchStrategy {
+            strategy: "binary",
+            wins: 1000,
+            games: 1000,
+            average: 5.8,
+            max: 7,
+        },
+        Msg::BenchBound { bound: 7, ok: true },
+        Msg::Replayed {
+            file: "game.json",
+            turns: 5,
+            seed: 42,
+        },
+        Msg::Listening("127.0.0.1:7878"),
+    ];
+    samples.extend(feedbacks.iter().map(|&f| Msg::Feedback(f)));
+    samples.extend(input_errors.iter().map(Msg::Input));
+    samples.extend(domain_errors.iter().map(Msg::Domain));
+    samples.extend(hints.iter().map(|&h| Msg::HintText(h)));
+    samples.extend(answers.iter().map(Msg::Answer));
+    samples.extend(contradictions.iter().map(Msg::Contradiction));
+    for &kind in kinds.iter() {
+        for &max in [Some(10), None].iter() {
+            samples.push(Msg::DomainRange {
+                kind,
+                low: "a",
+                high: "z",
+                max,
+            });
+        }
+        for &feedback in feedbacks.iter() {
+            samples.push(Msg::DomainFeedback { kind, feedback });
+        }
+    }
+
+    for &msg in samples.iter() {
+        covered(msg);
+        let texts: Vec<String> = Lang::ALL.iter().map(|lang| lang.text(msg)).collect();
+        for (lang, text) in Lang::ALL.iter().zip(texts.iter()) {
+            assert!(!text.trim().is_empty(), "{:?} is empty in {}", msg, lang);
+            assert!(!text.contains("{}"), "{:?} in {}: {}", msg, lang, text);
+        }
+        // Lang::ALL的第一个是英文
+        for (lang, text) in Lang::ALL.iter().zip(texts.iter()).skip(1) {
+            assert_ne!(text, &texts[0], "{:?} is not translated to {}", msg, lang);
+        }
+    }
+}
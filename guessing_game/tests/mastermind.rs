@@ -0,0 +1,16 @@
+// Knuth证明了经典规则（4位6色）下最多5次一定能猜中，对全部1296个答案检查一遍
+use guessing_game::mastermind::bench_knuth;
+use guessing_game::Rules;
+
+#[test]
+fn knuth_solves_classic_rules_in_five_guesses() {
+    let rules = Rules {
+        pegs: 4,
+        colours: 6,
+        max_attempts: None,
+    };
+    let report = bench_knuth(rules);
+    assert_eq!(report.games, 1296);
+    assert_eq!(report.wins, report.games);
+    assert!(report.max_attempts <= 5, "{}", report.max_attempts);
+}
@@ -0,0 +1,185 @@
+// 用脚本化的客户端连接真实的服务，按协议逐行检查服务端发出的消息
+use guessing_game::server::MAX_LINE;
+use guessing_game::{FixedSecret, GameConfig, Server, ServerConfig, ServerHandle};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const SECRET: u32 = 42;
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(server: &ServerHandle) -> Client {
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        // 消息没有按预期到达时让测试失败，而不是一直等下去
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        Client {
+            writer: stream.try_clone().unwrap(),
+            reader: BufReader::new(stream),
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.writer, "{}", line).unwrap();
+    }
+
+    fn expect(&mut self, expected: &str) {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim_end(), expected);
+    }
+
+    // 错误提示的内容来自输入解析，这里只检查类型
+    fn expect_line_starting_with(&mut self, prefix: &str) {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with(prefix), "{}", line);
+    }
+
+    // 服务端关闭连接后读到的是EOF
+    fn expect_closed(&mut self) {
+        let mut line = String::new();
+        assert_eq!(self.reader.read_line(&mut line).unwrap(), 0, "{}", line);
+    }
+}
+
+fn config(max_attempts: Option<u32>) -> ServerConfig {
+    ServerConfig {
+        game: GameConfig {
+            low: 1,
+            high: 100,
+            max_attempts,
+        },
+        ..ServerConfig::default()
+    }
+}
+
+fn start(max_attempts: Option<u32>) -> ServerHandle {
+    start_with(config(max_attempts))
+}
+
+fn start_with(config: ServerConfig) -> ServerHandle {
+    Server::bind("127.0.0.1:0", config, Box::new(FixedSecret(SECRET)))
+        .unwrap()
+        .spawn()
+        .unwrap()
+}
+
+// 两个玩家依次连接，返回时双方都已经读完了加入时的消息
+fn join_two(server: &ServerHandle) -> (Client, Client) {
+    let mut alice = Client::connect(server);
+    alice.expect("HELLO 1 player1");
+    alice.expect("ROUND 1 1 100");
+    let mut bob = Client::connect(server);
+    bob.expect("HELLO 2 player2");
+    bob.expect("ROUND 1 1 100");
+    alice.expect("JOIN player2");
+    (alice, bob)
+}
+
+#[test]
+fn two_players_guess_win_and_leave() {
+    let server = start(None);
+    let (mut alice, mut bob) = join_two(&server);
+
+    alice.send("NAME alice");
+    alice.expect("RENAME player1 alice");
+    bob.expect("RENAME player1 alice");
+
+    alice.send("30");
+    alice.expect("RESULT alice 30 too small");
+    bob.expect("RESULT alice 30 too small");
+
+    bob.send("GUESS 42");
+    for client in [&mut alice, &mut bob] {
+        client.expect("WINNER player2 42 1");
+        client.expect("ROUND 2 1 100");
+    }
+
+    alice.send("QUIT");
+    alice.expect("BYE quit");
+    alice.expect_closed();
+    bob.expect("LEAVE alice");
+
+    server.stop().unwrap();
+    bob.expect("BYE server stopped");
+    bob.expect_closed();
+}
+
+#[test]
+fn round_ends_when_last_player_with_attempts_leaves() {
+    let server = start(Some(1));
+    let (mut alice, mut bob) = join_two(&server);
+
+    alice.send("10");
+    alice.expect("RESULT player1 10 too small");
+    alice.expect("ERROR no attempts left, wait for the next round");
+    bob.expect("RESULT player1 10 too small");
+
+    bob.send("QUIT");
+    bob.expect("BYE quit");
+    alice.expect("LEAVE player2");
+    alice.expect("NOBODY 42");
+    alice.expect("ROUND 2 1 100");
+
+    server.stop().unwrap();
+    alice.expect("BYE server stopped");
+}
+
+#[test]
+fn invalid_input_only_goes_to_the_sender() {
+    let server = start(None);
+    let (mut alice, mut bob) = join_two(&server);
+
+    alice.send("abc");
+    alice.send("NAME two words");
+    alice.expect_line_starting_with("ERROR ");
+    alice.expect("ERROR name must be a single word");
+
+    // bob收到的下一条消息是自己的猜测结果，说明前面的错误没有广播
+    bob.send("99");
+    bob.expect("RESULT player2 99 too big");
+    alice.expect("RESULT player2 99 too big");
+}
+
+#[test]
+fn idle_player_is_disconnected() {
+    let server = start_with(ServerConfig {
+        idle_timeout: Duration::from_millis(300),
+        ..config(None)
+    });
+    let mut alice = Client::connect(&server);
+    alice.expect("HELLO 1 player1");
+    alice.expect("ROUND 1 1 100");
+    alice.expect("BYE idle timeout");
+    alice.expect_closed();
+
+    // 断开的玩家已经离开，新加入的玩家开始新的一轮
+    let mut bob = Client::connect(&server);
+    bob.expect("HELLO 2 player2");
+    bob.expect("ROUND 2 1 100");
+}
+
+#[test]
+fn overlong_line_disconnects_the_sender() {
+    let server = start(None);
+    let (mut alice, mut bob) = join_two(&server);
+
+    // 正好MAX_LINE字节的一行可以接受
+    let name = "a".repeat(MAX_LINE - "NAME ".len());
+    alice.send(&format!("NAME {}", name));
+    alice.expect(&format!("RENAME player1 {}", name));
+    bob.expect(&format!("RENAME player1 {}", name));
+
+    // 比上限多一个字节并且没有换行，服务端会读完这些字节，关闭连接时没有未读的数据
+    alice.writer.write_all(&[b'1'; MAX_LINE + 1]).unwrap();
+    alice.expect("BYE line too long");
+    alice.expect_closed();
+    bob.expect(&format!("LEAVE {}", name));
+}
@@ -0,0 +1,101 @@
+// 比较cowstr和总是返回String的写法各自分配了多少次内存
+// 用一个计数的全局分配器包住系统分配器，每个操作在同样的输入上各跑一遍
+use rust101::cowstr;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// 只统计分配的次数，realloc也算一次，释放不算
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// 大部分输入已经是处理好的样子，这正是Cow省掉分配的场景
+const INPUTS: [&str; 6] = [
+    "already clean",
+    "lowercase words only",
+    "  padded  ",
+    "Mixed Case Title",
+    "tabs\tand\nnewlines",
+    "内存安全 no change",
+];
+
+// 运行f并返回期间分配的次数，调用方用black_box防止结果被优化掉
+fn count<F: FnMut()>(mut f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    let rounds = 1000;
+    println!(
+        "{} inputs x {} rounds, total allocations",
+        INPUTS.len(),
+        rounds
+    );
+    println!("{:<22} {:>10} {:>10}", "operation", "cow", "owned");
+
+    let run = |name: &str, cow: &dyn Fn(&str) -> usize, owned: &dyn Fn(&str) -> usize| {
+        let cow = count(|| {
+            for _ in 0..rounds {
+                for input in INPUTS.iter() {
+                    std::hint::black_box(cow(input));
+                }
+            }
+        });
+        let owned = count(|| {
+            for _ in 0..rounds {
+                for input in INPUTS.iter() {
+                    std::hint::black_box(owned(input));
+                }
+            }
+        });
+        println!("{:<22} {:>10} {:>10}", name, cow, owned);
+    };
+
+    run("trim", &|s| cowstr::trim(s).len(), &|s| {
+        s.trim().to_string().len()
+    });
+    run("fold_case", &|s| cowstr::fold_case(s).len(), &|s| {
+        s.to_lowercase().len()
+    });
+    run("replace", &|s| cowstr::replace(s, "\t", " ").len(), &|s| {
+        s.replace('\t', " ").len()
+    });
+    run(
+        "normalize_whitespace",
+        &|s| cowstr::normalize_whitespace(s).len(),
+        &|s| s.split_whitespace().collect::<Vec<_>>().join(" ").len(),
+    );
+    // 传入String时所有权交给函数，trim在原来的内存上修改，不会再分配
+    run(
+        "trim (owned input)",
+        &|s| {
+            let owned = s.to_string();
+            cowstr::trim(owned).len()
+        },
+        &|s| {
+            let owned = s.to_string();
+            owned.trim().to_string().len()
+        },
+    );
+}
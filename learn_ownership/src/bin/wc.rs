@@ -0,0 +1,107 @@
+// 类似wc的文本统计命令，统计逻辑在textstats里
+// 用法: wc [--json] [file]...，没有文件或者文件名是-时读取标准输入
+use rust101::textstats::TextStats;
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::{env, process};
+
+const USAGE: &str = "usage: wc [--json] [file]...";
+
+// JSON输出的一项，字段和TextStats一样，多了文件名
+#[derive(Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    stats: &'a TextStats,
+}
+
+fn main() {
+    let mut json = false;
+    let mut files = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "-" => files.push(arg),
+            other if other.starts_with('-') => {
+                eprintln!("unknown argument: {}\n{}", other, USAGE);
+                process::exit(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        files.push(String::from("-"));
+    }
+
+    // 某个文件读取失败时继续统计其他文件，最后以非0退出
+    let mut failed = false;
+    let mut results = Vec::new();
+    for file in &files {
+        match count(file) {
+            Ok(stats) => results.push((file.as_str(), stats)),
+            Err(e) => {
+                eprintln!("wc: {}: {}", file, e);
+                failed = true;
+            }
+        }
+    }
+
+    // 多个文件时和wc一样加一行总计
+    let mut total = TextStats::default();
+    for (_, stats) in &results {
+        total.merge(stats);
+    }
+    if results.len() > 1 {
+        results.push(("total", total));
+    }
+
+    if json {
+        let reports: Vec<Report> = results
+            .iter()
+            .map(|(file, stats)| Report { file, stats })
+            .collect();
+        match serde_json::to_string_pretty(&reports) {
+            Ok(text) => println!("{}", text),
+            Err(e) => {
+                eprintln!("wc: {}", e);
+                failed = true;
+            }
+        }
+    } else {
+        println!(
+            "{:>8} {:>8} {:>8} {:>8} {:>9}  {:<16} file",
+            "lines", "words", "chars", "bytes", "graphemes", "longest"
+        );
+        for (file, stats) in &results {
+            println!(
+                "{:>8} {:>8} {:>8} {:>8} {:>9}  {:<16} {}",
+                stats.lines,
+                stats.words,
+                stats.chars,
+                stats.bytes,
+                stats.graphemes,
+                stats.longest_word,
+                file
+            );
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn count(file: &str) -> io::Result<TextStats> {
+    if file == "-" {
+        let stdin = io::stdin();
+        let lock = stdin.lock();
+        TextStats::from_reader(lock)
+    } else {
+        TextStats::from_reader(File::open(file)?)
+    }
+}
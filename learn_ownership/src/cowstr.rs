@@ -0,0 +1,78 @@
+// 写时复制的字符串处理：参数既可以是借用的&str，也可以是拥有所有权的String，
+// 返回Cow<str>，只有真的需要修改内容时才分配新的内存
+// 传入&str并且不需要修改时，返回的是原字符串的切片，一次分配都没有；
+// 传入String时尽量在原来的内存上修改，比如trim只是截断和移动，不会重新分配
+use std::borrow::Cow;
+
+// 去掉首尾的空白字符
+pub fn trim<'a, S: Into<Cow<'a, str>>>(s: S) -> Cow<'a, str> {
+    match s.into() {
+        Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+        Cow::Owned(mut s) => {
+            let end = s.trim_end().len();
+            s.truncate(end);
+            let start = s.len() - s.trim_start().len();
+            s.drain(..start);
+            Cow::Owned(s)
+        }
+    }
+}
+
+// 转成小写用于不区分大小写的比较，这里用的是to_lowercase，不是完整的Unicode大小写折叠，
+// 比如ß不会变成ss
+pub fn fold_case<'a, S: Into<Cow<'a, str>>>(s: S) -> Cow<'a, str> {
+    let s = s.into();
+    if s.chars().all(is_lowercase_fixed_point) {
+        s
+    } else {
+        Cow::Owned(s.to_lowercase())
+    }
+}
+
+// 小写之后还是自己的字符，包括本来就是小写的、数字、标点和没有大小写的文字
+fn is_lowercase_fixed_point(c: char) -> bool {
+    let mut lower = c.to_lowercase();
+    lower.next() == Some(c) && lower.next().is_none()
+}
+
+// 和str::replace一样替换所有的from，找不到from时原样返回
+pub fn replace<'a, S: Into<Cow<'a, str>>>(s: S, from: &str, to: &str) -> Cow<'a, str> {
+    let s = s.into();
+    if s.contains(from) {
+        Cow::Owned(s.replace(from, to))
+    } else {
+        s
+    }
+}
+
+// 把连续的空白（包括换行、制表符和不换行空格）合并成一个空格，并去掉首尾的空白
+pub fn normalize_whitespace<'a, S: Into<Cow<'a, str>>>(s: S) -> Cow<'a, str> {
+    let s = trim(s);
+    if is_normalized(&s) {
+        return s;
+    }
+    let mut out = String::with_capacity(s.len());
+    for word in s.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    Cow::Owned(out)
+}
+
+// 已经去掉首尾空白之后，只要没有空格以外的空白，也没有连续的空格就不需要修改
+fn is_normalized(s: &str) -> bool {
+    let mut previous_space = false;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            if c != ' ' || previous_space {
+                return false;
+            }
+            previous_space = true;
+        } else {
+            previous_space = false;
+        }
+    }
+    true
+}
@@ -0,0 +1,167 @@
+// 斐波那契数列：F(0) = 0, F(1) = 1, F(n) = F(n-1) + F(n-2)
+// i32在F(47)就溢出了，这里统一返回任意精度的BigInt，同时提供三种算法：
+// 逐项迭代O(n)，快速倍增和矩阵快速幂都是O(log n)次大数乘法
+// 负数下标按负斐波那契数列处理：F(-n) = (-1)^(n+1) * F(n)，比如F(-1) = 1, F(-2) = -1
+use crate::recurrence::Recurrence;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Zero};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Iterative,
+    Doubling,
+    Matrix,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Iterative, Algorithm::Doubling, Algorithm::Matrix];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Iterative => "iterative",
+            Algorithm::Doubling => "doubling",
+            Algorithm::Matrix => "matrix",
+        }
+    }
+}
+
+// 惰性地生成F(0), F(1), F(2) ...，下一项超出u64时停止，最后一项是F(93)
+// 需要逐项处理时用它配合take_while，不要在循环里反复调用fib(i)
+pub fn iter() -> Recurrence<u64> {
+    Recurrence::fibonacci()
+}
+
+// 和iter一样，但是用大数，永远不会停止
+pub fn iter_big() -> Recurrence<BigUint> {
+    Recurrence::fibonacci()
+}
+
+// 默认使用快速倍增，三种算法里最快
+pub fn fib(n: i64) -> BigInt {
+    fib_with(n, Algorithm::Doubling)
+}
+
+pub fn fib_with(n: i64, algorithm: Algorithm) -> BigInt {
+    let k = n.unsigned_abs();
+    let value = match algorithm {
+        Algorithm::Iterative => iterative(k),
+        Algorithm::Doubling => doubling(k).0,
+        Algorithm::Matrix => matrix(k),
+    };
+    // 负数下标且是偶数时结果为负
+    if n < 0 && k.is_multiple_of(2) {
+        -BigInt::from(value)
+    } else {
+        BigInt::from(value)
+    }
+}
+
+// 和原来的fib一样逐项相加，只是换成了大数
+fn iterative(n: u64) -> BigUint {
+    let mut x = BigUint::zero();
+    let mut y = BigUint::one();
+    for _ in 0..n {
+        let z = &x + &y;
+        x = y;
+        y = z;
+    }
+    x
+}
+
+// 快速倍增，返回(F(n), F(n+1))，用到的两个恒等式：
+// F(2k) = F(k) * (2F(k+1) - F(k))
+// F(2k+1) = F(k)^2 + F(k+1)^2
+// 从n的最高位开始，每一位先把k翻倍，这一位是1的话再往前走一步
+fn doubling(n: u64) -> (BigUint, BigUint) {
+    let mut a = BigUint::zero();
+    let mut b = BigUint::one();
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        // F(k+1) >= F(k)，所以2F(k+1) - F(k)不会是负数
+        let c = &a * ((&b << 1usize) - &a);
+        let d = &a * &a + &b * &b;
+        if (n >> bit) & 1 == 1 {
+            b = &c + &d;
+            a = d;
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    (a, b)
+}
+
+// [[1, 1], [1, 0]]的n次方等于[[F(n+1), F(n)], [F(n), F(n-1)]]，用快速幂计算
+fn matrix(n: u64) -> BigUint {
+    type Matrix = [[BigUint; 2]; 2];
+    fn mul(x: &Matrix, y: &Matrix) -> Matrix {
+        let cell = |i: usize, j: usize| &x[i][0] * &y[0][j] + &x[i][1] * &y[1][j];
+        [[cell(0, 0), cell(0, 1)], [cell(1, 0), cell(1, 1)]]
+    }
+
+    let mut result: Matrix = [
+        [BigUint::one(), BigUint::zero()],
+        [BigUint::zero(), BigUint::one()],
+    ];
+    let mut base: Matrix = [
+        [BigUint::one(), BigUint::one()],
+        [BigUint::one(), BigUint::zero()],
+    ];
+    let mut n = n;
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mul(&result, &base);
+        }
+        base = mul(&base, &base);
+        n >>= 1;
+    }
+    let [[_, f], _] = result;
+    f
+}
+
+// F(n) mod m，用快速倍增并且每一步都取模，不需要算出完整的大数
+// 中间结果用u128保存，m不超过u64就不会溢出，m为0时和整数取模一样会panic
+pub fn fib_mod(n: i64, m: u64) -> u64 {
+    assert!(m > 0, "modulus must be positive");
+    let m = u128::from(m);
+    let k = n.unsigned_abs();
+
+    let mut a: u128 = 0;
+    let mut b: u128 = 1 % m;
+    for bit in (0..64 - k.leading_zeros()).rev() {
+        let c = a * ((2 * b + m - a) % m) % m;
+        let d = (a * a % m + b * b % m) % m;
+        if (k >> bit) & 1 == 1 {
+            b = (c + d) % m;
+            a = d;
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    if n < 0 && k.is_multiple_of(2) {
+        ((m - a) % m) as u64
+    } else {
+        a as u64
+    }
+}
+
+// 皮萨诺周期：F(n) mod m 是周期数列，周期是π(m)，所以F(n) ≡ F(n mod π(m)) (mod m)
+// 数列回到(0, 1)的位置就是一个周期，π(m)不超过6m，所以这里是O(m)的
+pub fn pisano_period(m: u64) -> u64 {
+    assert!(m > 0, "modulus must be positive");
+    if m == 1 {
+        return 1;
+    }
+    let m = u128::from(m);
+    let (mut a, mut b) = (0u128, 1u128);
+    let mut period = 0;
+    loop {
+        let c = (a + b) % m;
+        a = b;
+        b = c;
+        period += 1;
+        if a == 0 && b == 1 {
+            return period;
+        }
+    }
+}
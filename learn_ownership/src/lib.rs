@@ -0,0 +1,6 @@
+// 从main里整理出来可以复用的部分，main继续保留讲解所有权的示例代码
+pub mod cowstr;
+pub mod fibonacci;
+pub mod recurrence;
+pub mod textstats;
+pub mod tokenizer;
@@ -1,3 +1,9 @@
+use rust101::cowstr;
+use rust101::fibonacci;
+use rust101::recurrence::Recurrence;
+use rust101::tokenizer;
+use std::borrow::Cow;
+
 // 常量必须使用const声明，同时必须指定类型，而且常量无法修改，无法声明为mut
 const MAX_RANGE: u64 = 100_000;
 
@@ -13,7 +19,8 @@ fn main() {
     // 通过let声明同名变量 对之前变量进行隐藏
     let x = 'a';
     println!("Hello, world!");
-    println!("fbi: {}", fib(2));
+    println!("fbi: {}", fibonacci::fib(2));
+    fibonacci_demo();
 
     // 记住所有权规则：
     // 1.每一个值都有一个对应的变量作为所有者；
@@ -57,6 +64,20 @@ fn main() {
     // 所有权在函数入参和返回值进行转移
     let mut s1 = take_ownership_v2(s1);
 
+    // 如果函数有时需要修改字符串有时不需要，可以接收impl Into<Cow<str>>，
+    // 这样调用方既可以转移所有权也可以只借用，不需要修改时不会分配新的内存
+    let trimmed = cowstr::trim("  borrowed  ");
+    let folded = cowstr::fold_case(String::from("Owned String"));
+    let normalized = cowstr::normalize_whitespace("already normalized");
+    println!(
+        "trim: {:?}, fold_case: {:?}, normalize_whitespace: {:?}",
+        trimmed, folded, normalized
+    );
+    // Cow::Borrowed说明没有复制，Cow::Owned说明分配了新的字符串或者用的是传入的String
+    assert!(matches!(normalized, Cow::Borrowed(_)));
+    assert_eq!(cowstr::normalize_whitespace(" a\t\u{a0}b\n\nc "), "a b c");
+    assert_eq!(cowstr::replace("a-b-c", "-", "+"), "a+b+c");
+
     // 对于频繁在函数转移所有权回 很麻烦，rust通过引用来解决这个问题
     // 引用时会生成一个指针指向原值，同时不会发生所有权转移，引用的传递也不会影响原值，所以引用又叫借用，只是暂时借用不影响所有权
     // 引用默认也是不可变的，同时可以创建多个不可变的引用
@@ -87,39 +108,66 @@ fn main() {
     // 创建字符串切片，必须使用&v[..]，这是一个完整字符的数组，类型是&str
     // 可以增加前后索引指定子串切片，比如 &v[1..10] &v[..10] &v[1..]
     let s1 = &hw[..];
-    let w = findFirstWord(&hw[..]);
+    // first_word返回的单词是hw的切片，类型是Option<Word>，其中的text是&str
+    let w = tokenizer::first_word(&hw[..]);
     // 因为w已经是不可变引用，所以不能调用clear传入可变引用了
     // hw.clear();
-    println!("first word: {}", w);
+    println!("first word: {:?}", w.map(|w| w.text));
+
+    // 按Unicode的规则切分，制表符、换行、不换行空格和中文都能正确处理
+    let text = "Rust\tis\u{a0}fast, 内存安全！\nno GC.";
+    for word in tokenizer::words(text) {
+        println!("word {:?} at {}..{}", word.text, word.start, word.end());
+    }
+    println!(
+        "last word: {:?}, third word: {:?}",
+        tokenizer::last_word(text).map(|w| w.text),
+        tokenizer::nth_word(text, 2).map(|w| w.text)
+    );
 
     let arr = [1, 2, 3, 4];
     // 如下是数组类型 [i32; 4]的切片，类型为 &[i32]
     let a1 = &arr[..];
 }
 
-fn findFirstWord(s: &str) -> &str{
-    let bytes = s.as_bytes();
-    for (i, &b) in bytes.iter().enumerate() {
-        if b == b' ' {
-            return &s[..i];
-        }
-    }
-    return s
-}
-
-fn fib(n: i32) ->i32 {
-    if n == 0 {
-        return 0
-    }
-
-    let mut x = 0;
-    let mut y = 1;
-    for i in (2..n+1).rev() {
-        let z = x + y;
-        x = y;
-        y = z;
-    }
-    y
+// 原来的fib(n: i32) -> i32在n=47时溢出，现在放在fibonacci模块里返回大数
+fn fibonacci_demo() {
+    // F(100)早就超出了u64
+    println!("fib(100): {}", fibonacci::fib(100));
+    // 负数下标: F(-1) = 1, F(-2) = -1, F(-3) = 2 ...
+    println!("fib(-8): {}", fibonacci::fib(-8));
+
+    // F(n) mod m以皮萨诺周期循环，比如π(10) = 60，算法之间互相验证的检查在tests/fibonacci.rs
+    println!("pisano period of 10: {}", fibonacci::pisano_period(10));
+
+    // 迭代器是惰性的，只算用到的项，可以和take_while、filter等组合使用
+    let even: Vec<u64> = fibonacci::iter()
+        .take_while(|&f| f < 1000)
+        .filter(|f| f % 2 == 0)
+        .collect();
+    println!("even fibonacci below 1000: {:?}", even);
+    // u64在F(93)之后溢出，迭代器在溢出前停止，而大数版本可以继续
+    println!("u64 fibonacci terms: {}", fibonacci::iter().count());
+    println!(
+        "fib(100) by iterator: {}",
+        fibonacci::iter_big().nth(100).unwrap_or_default()
+    );
+
+    // 其他线性递推数列只是系数和初始项不同
+    let lucas: Vec<u64> = Recurrence::lucas().take(10).collect();
+    let tribonacci: Vec<u64> = Recurrence::tribonacci().take(10).collect();
+    let pell: Vec<u64> = Recurrence::pell().take(10).collect();
+    println!(
+        "lucas: {:?}\ntribonacci: {:?}\npell: {:?}",
+        lucas, tribonacci, pell
+    );
+    // 系数可以是负数，a(n) = 2a(n-1) - a(n-2)就是等差数列
+    let arithmetic: Vec<i64> = Recurrence::new(vec![2, -1], vec![3, 7]).take(5).collect();
+    println!("arithmetic: {:?}", arithmetic);
+    println!(
+        "fib(10^18) mod 1_000_000_007: {}",
+        fibonacci::fib_mod(1_000_000_000_000_000_000, 1_000_000_007)
+    );
 }
 
 fn take_ownership(s: String) {
@@ -0,0 +1,98 @@
+// 常系数线性递推数列：a(n) = c[0] * a(n-1) + c[1] * a(n-2) + ... + c[k-1] * a(n-k)
+// 给定k个系数和前k项就能惰性地生成整个数列，斐波那契、卢卡斯、三阶斐波那契、佩尔数都是特例
+// 元素类型是泛型的：用u64时下一项溢出就停止，用BigUint/BigInt时可以一直算下去
+use num_traits::{CheckedAdd, CheckedMul, Zero};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct Recurrence<T> {
+    coefficients: Vec<T>,
+    // 最近的k项，最早的在前面，下一次返回的就是第一项
+    window: VecDeque<T>,
+    // 下一项已经溢出，只把窗口里剩下的项返回完就结束
+    overflowed: bool,
+}
+
+impl<T> Recurrence<T>
+where
+    T: Clone + Zero + CheckedAdd + CheckedMul,
+{
+    // 系数和初始项的个数必须相同，不相同说明用法有误，直接panic
+    pub fn new(coefficients: Vec<T>, seeds: Vec<T>) -> Recurrence<T> {
+        assert_eq!(
+            coefficients.len(),
+            seeds.len(),
+            "a recurrence of order k needs k coefficients and k seeds"
+        );
+        Recurrence {
+            coefficients,
+            window: seeds.into(),
+            overflowed: false,
+        }
+    }
+
+    // 下一项有没有溢出，u64的斐波那契数列在F(93)之后溢出
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    fn next_term(&self) -> Option<T> {
+        let k = self.window.len();
+        self.coefficients
+            .iter()
+            .enumerate()
+            .try_fold(T::zero(), |sum, (i, c)| {
+                c.checked_mul(&self.window[k - 1 - i])
+                    .and_then(|term| sum.checked_add(&term))
+            })
+    }
+}
+
+impl<T> Recurrence<T>
+where
+    T: Clone + Zero + CheckedAdd + CheckedMul + From<u8>,
+{
+    // 0, 1, 1, 2, 3, 5, 8 ...
+    pub fn fibonacci() -> Recurrence<T> {
+        Recurrence::from_small(&[1, 1], &[0, 1])
+    }
+
+    // 2, 1, 3, 4, 7, 11 ...
+    pub fn lucas() -> Recurrence<T> {
+        Recurrence::from_small(&[1, 1], &[2, 1])
+    }
+
+    // 0, 0, 1, 1, 2, 4, 7, 13 ...
+    pub fn tribonacci() -> Recurrence<T> {
+        Recurrence::from_small(&[1, 1, 1], &[0, 0, 1])
+    }
+
+    // a(n) = 2a(n-1) + a(n-2)：0, 1, 2, 5, 12, 29 ...
+    pub fn pell() -> Recurrence<T> {
+        Recurrence::from_small(&[2, 1], &[0, 1])
+    }
+
+    fn from_small(coefficients: &[u8], seeds: &[u8]) -> Recurrence<T> {
+        Recurrence::new(
+            coefficients.iter().map(|&c| T::from(c)).collect(),
+            seeds.iter().map(|&s| T::from(s)).collect(),
+        )
+    }
+}
+
+impl<T> Iterator for Recurrence<T>
+where
+    T: Clone + Zero + CheckedAdd + CheckedMul,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if !self.overflowed && !self.window.is_empty() {
+            match self.next_term() {
+                Some(term) => self.window.push_back(term),
+                None => self.overflowed = true,
+            }
+        }
+        self.window.pop_front()
+    }
+}
@@ -0,0 +1,238 @@
+// 类似wc的文本统计：字节、字符、字形簇（用户看到的一个“字”）、单词、行数和最长的单词
+// 单词用tokenizer按UAX #29切分，和wc只按空白切分不同，标点不算单词
+use crate::tokenizer;
+use serde::Serialize;
+use std::io::{self, Read};
+use std::str;
+use unicode_segmentation::UnicodeSegmentation;
+
+// 每次读取的字节数
+const CHUNK: usize = 64 * 1024;
+// 切分点之后至少留这么多字节，UAX #29判断边界时会往后看几个字符，
+// 留在后面的部分等读到下一块再一起统计
+const LOOKAHEAD: usize = 256;
+// 在片段中间切开时最多往前找这么多字节，判断一个位置时只看前后WINDOW字节
+const CUT_SEARCH: usize = 1024;
+const WINDOW: usize = 32;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TextStats {
+    pub bytes: u64,
+    pub chars: u64,
+    pub graphemes: u64,
+    pub words: u64,
+    // 和wc一样数换行符，最后一行没有换行时不算
+    pub lines: u64,
+    // 按字符数比较，一样长时保留先出现的
+    pub longest_word: String,
+}
+
+impl TextStats {
+    // 统计一段完整的文本，多次调用时每段文本中间不能把单词或者字形簇截断
+    pub fn add_str(&mut self, text: &str) {
+        self.bytes += text.len() as u64;
+        self.count_text(text);
+    }
+
+    // 按固定大小的块读取并统计，只解码新读到的字节，内存占用和输入有没有换行无关
+    // 每次统计到离结尾LOOKAHEAD字节以前的最后一个单词边界，后面的文本留到读完下一块再说
+    // 一个单词比LOOKAHEAD还长时，在单词中间找一个安全的位置，前面的字符和字形簇先统计掉，
+    // 只留着单词的文本用来比较最长的单词，空白这种不可能成为单词的片段连文本也不留
+    // 不是合法UTF-8的字节按替换字符U+FFFD计数，字节数仍然是原始的字节数
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<TextStats> {
+        let mut stats = TextStats::default();
+        let mut buf = vec![0; CHUNK];
+        // 上一块结尾被截断的UTF-8字节，最多3个
+        let mut incomplete = Vec::new();
+        // 已经解码但是还不能确定边界的文本
+        let mut tail = String::new();
+        let mut open = None;
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            stats.bytes += n as u64;
+            incomplete.extend_from_slice(&buf[..n]);
+            let used = decode_into(&incomplete, &mut tail);
+            incomplete.drain(..used);
+            stats.count_ready(&mut tail, &mut open);
+        }
+        tail.push_str(&String::from_utf8_lossy(&incomplete));
+        stats.count_segments(&tail, &mut open);
+        if let Some(open) = open.take() {
+            stats.finish_word(open);
+        }
+        Ok(stats)
+    }
+
+    // 合并多个文件的统计，用于输出总计
+    pub fn merge(&mut self, other: &TextStats) {
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.graphemes += other.graphemes;
+        self.words += other.words;
+        self.lines += other.lines;
+        self.update_longest(&other.longest_word);
+    }
+
+    fn count_text(&mut self, text: &str) {
+        self.count_chars(text);
+        for word in tokenizer::words(text) {
+            self.words += 1;
+            self.update_longest(word.text);
+        }
+    }
+
+    fn update_longest(&mut self, word: &str) {
+        if word.chars().count() > self.longest_word.chars().count() {
+            self.longest_word = word.to_string();
+        }
+    }
+
+    // 字符、字形簇和行数，text的两端都要在字形簇的边界上
+    fn count_chars(&mut self, text: &str) {
+        self.chars += text.chars().count() as u64;
+        self.graphemes += text.graphemes(true).count() as u64;
+        self.lines += text.bytes().filter(|&b| b == b'\n').count() as u64;
+    }
+
+    // 统计tail里已经能确定边界的部分，并把它从tail里去掉
+    fn count_ready(&mut self, tail: &mut String, open: &mut Option<OpenSegment>) {
+        // 攒够两倍LOOKAHEAD再切分，每个字节平均只会被切分常数次
+        if tail.len() < 2 * LOOKAHEAD {
+            return;
+        }
+        // 结束位置不超过end的片段都可以统计了
+        let end = tail.len() - LOOKAHEAD;
+        let mut cut = 0;
+        for (start, segment) in tail.split_word_bound_indices() {
+            if start + segment.len() > end {
+                break;
+            }
+            self.add_segment(segment, open);
+            cut = start + segment.len();
+        }
+        if cut > 0 {
+            self.count_chars(&tail[..cut]);
+            tail.drain(..cut);
+            return;
+        }
+        // 第一个片段一直延伸到end之后，只能在片段中间切开
+        if let Some(cut) = inner_cut(tail, end) {
+            self.count_chars(&tail[..cut]);
+            let part = &tail[..cut];
+            let segment = open.get_or_insert_with(|| OpenSegment {
+                text: String::new(),
+                is_word: false,
+                keep_text: !part.starts_with(char::is_whitespace),
+            });
+            segment.is_word |= tokenizer::is_word(part);
+            if segment.keep_text {
+                segment.text.push_str(part);
+            }
+            tail.drain(..cut);
+        }
+    }
+
+    // 统计一段在单词边界上结束的文本
+    fn count_segments(&mut self, text: &str, open: &mut Option<OpenSegment>) {
+        self.count_chars(text);
+        for segment in text.split_word_bounds() {
+            self.add_segment(segment, open);
+        }
+    }
+
+    // open不为空时这个片段接在它后面，是同一个单词的结尾
+    fn add_segment(&mut self, segment: &str, open: &mut Option<OpenSegment>) {
+        if let Some(mut open) = open.take() {
+            open.is_word |= tokenizer::is_word(segment);
+            if open.keep_text {
+                open.text.push_str(segment);
+            }
+            self.finish_word(open);
+        } else if tokenizer::is_word(segment) {
+            self.words += 1;
+            self.update_longest(segment);
+        }
+    }
+
+    fn finish_word(&mut self, segment: OpenSegment) {
+        if segment.is_word {
+            self.words += 1;
+            self.update_longest(&segment.text);
+        }
+    }
+}
+
+// 前面的字符已经统计过、还没有结束的片段
+struct OpenSegment {
+    text: String,
+    is_word: bool,
+    // 空白开头的片段只会是一串空白，不会成为单词，不用留着文本
+    keep_text: bool,
+}
+
+// 在第一个片段中间找一个切开后不影响统计的位置，从end往前找
+// 要求两边都是字母、数字、下划线或者空格，这里既是字形簇的边界又不是单词的边界，
+// 并且后一个字符不会用到更前面的字符来判断边界，从这里重新切分得到的第一个片段就是原来片段的后半截
+// 只有一个字形簇本身很长（比如一长串组合符号）时会找不到，这时只能继续攒着
+fn inner_cut(text: &str, end: usize) -> Option<usize> {
+    let simple = |c: char| c.is_alphanumeric() || c == '_' || c == ' ';
+    let mut cut = end;
+    let lowest = end.saturating_sub(CUT_SEARCH);
+    while cut > lowest {
+        if text.is_char_boundary(cut) {
+            let before = text[..cut].chars().next_back().filter(|&c| simple(c));
+            let after = text[cut..].chars().next().filter(|&c| simple(c));
+            if before.is_some() && after.is_some() && splits_inside(text, cut) {
+                return Some(cut);
+            }
+        }
+        cut -= 1;
+    }
+    None
+}
+
+// 只看cut附近的一小段，判断cut是字形簇的边界、但不是单词的边界
+fn splits_inside(text: &str, cut: usize) -> bool {
+    let mut lo = cut.saturating_sub(WINDOW);
+    while !text.is_char_boundary(lo) {
+        lo -= 1;
+    }
+    let mut hi = (cut + WINDOW).min(text.len());
+    while !text.is_char_boundary(hi) {
+        hi += 1;
+    }
+    let window = &text[lo..hi];
+    let at = cut - lo;
+    window.grapheme_indices(true).any(|(i, _)| i == at)
+        && !window.split_word_bound_indices().any(|(i, _)| i == at)
+}
+
+// 和from_utf8_lossy一样把不合法的字节换成U+FFFD追加到text后面，但是结尾被截断的字符不替换，
+// 返回解码了多少字节，剩下的是被截断的字符
+fn decode_into(bytes: &[u8], text: &mut String) -> usize {
+    let mut rest = bytes;
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                return bytes.len();
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                text.push_str(str::from_utf8(valid).unwrap());
+                match e.error_len() {
+                    Some(len) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[len..];
+                    }
+                    None => return bytes.len() - after.len(),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// 按Unicode的单词边界规则（UAX #29）切分单词，代替原来只认ASCII空格的findFirstWord
+// 制表符、换行、不换行空格都算分隔，标点不算单词，中文等没有空格的文字每个字是一个词
+// 返回的单词都是原字符串的切片，不会复制，所以原字符串在单词用完之前不能修改
+use unicode_segmentation::{UWordBoundIndices, UnicodeSegmentation};
+
+// 一个单词和它在原字符串里的字节偏移，可以用&s[word.start..word.end()]取回同样的切片
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word<'a> {
+    pub text: &'a str,
+    pub start: usize,
+}
+
+impl<'a> Word<'a> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+// 零拷贝的单词迭代器，两端都可以取，所以最后一个单词不用从头遍历
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+    bounds: UWordBoundIndices<'a>,
+}
+
+pub fn words(s: &str) -> Words<'_> {
+    Words {
+        bounds: s.split_word_bound_indices(),
+    }
+}
+
+// 边界之间的片段可能是空白或者标点，至少包含一个字母或数字的才算单词
+pub(crate) fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = Word<'a>;
+
+    fn next(&mut self) -> Option<Word<'a>> {
+        self.bounds
+            .by_ref()
+            .find(|(_, segment)| is_word(segment))
+            .map(|(start, text)| Word { text, start })
+    }
+}
+
+impl<'a> DoubleEndedIterator for Words<'a> {
+    fn next_back(&mut self) -> Option<Word<'a>> {
+        self.bounds
+            .by_ref()
+            .rev()
+            .find(|(_, segment)| is_word(segment))
+            .map(|(start, text)| Word { text, start })
+    }
+}
+
+pub fn first_word(s: &str) -> Option<Word<'_>> {
+    words(s).next()
+}
+
+pub fn last_word(s: &str) -> Option<Word<'_>> {
+    words(s).next_back()
+}
+
+// 从0开始计数
+pub fn nth_word(s: &str, n: usize) -> Option<Word<'_>> {
+    words(s).nth(n)
+}
@@ -0,0 +1,108 @@
+// 三种算法、取模和迭代器的结果互相验证
+use num_bigint::BigInt;
+use rust101::fibonacci::{self, Algorithm};
+
+#[test]
+fn known_values() {
+    let cases = [
+        (0, "0"),
+        (1, "1"),
+        (2, "1"),
+        (10, "55"),
+        // i32在F(47)溢出，u64在F(94)溢出
+        (47, "2971215073"),
+        (94, "19740274219868223167"),
+        (100, "354224848179261915075"),
+        (-1, "1"),
+        (-2, "-1"),
+        (-8, "-21"),
+        (-9, "34"),
+    ];
+    for &(n, expected) in cases.iter() {
+        assert_eq!(fibonacci::fib(n).to_string(), expected, "fib({})", n);
+    }
+}
+
+#[test]
+fn algorithms_agree() {
+    for n in -100..=300 {
+        let expected = fibonacci::fib_with(n, Algorithm::Iterative);
+        for &algorithm in Algorithm::ALL.iter() {
+            assert_eq!(
+                fibonacci::fib_with(n, algorithm),
+                expected,
+                "{} at {}",
+                algorithm.name(),
+                n
+            );
+        }
+    }
+}
+
+// F(-n) = (-1)^(n+1) * F(n)
+#[test]
+fn negative_indices() {
+    for n in 1..=100i64 {
+        let sign = if n % 2 == 0 { -1 } else { 1 };
+        assert_eq!(fibonacci::fib(-n), fibonacci::fib(n) * sign, "fib({})", -n);
+    }
+}
+
+// 取模的结果和大数取模一致，并且以皮萨诺周期循环
+#[test]
+fn fib_mod_matches_big_numbers() {
+    for m in 1..=50u64 {
+        let period = fibonacci::pisano_period(m);
+        for n in -60..=200i64 {
+            let expected = fibonacci::fib(n) % m;
+            let expected = ((expected + m) % m).to_string();
+            assert_eq!(
+                fibonacci::fib_mod(n, m).to_string(),
+                expected,
+                "fib_mod({}, {})",
+                n,
+                m
+            );
+            assert_eq!(
+                fibonacci::fib_mod(n + period as i64, m),
+                fibonacci::fib_mod(n, m)
+            );
+        }
+    }
+}
+
+#[test]
+fn fib_mod_large_inputs() {
+    // 下标很大时按周期换成小下标验证
+    let n = 1_000_000_000_000_000_000;
+    assert_eq!(fibonacci::fib_mod(n, 10), fibonacci::fib_mod(n % 60, 10));
+    assert_eq!(fibonacci::fib_mod(i64::MIN, 1), 0);
+    // 模数接近u64::MAX时中间结果也不会溢出
+    let m = u64::MAX;
+    assert_eq!(
+        fibonacci::fib_mod(300, m).to_string(),
+        (fibonacci::fib(300) % m).to_string()
+    );
+}
+
+#[test]
+fn pisano_periods() {
+    let periods = [(1, 1), (2, 3), (3, 8), (5, 20), (10, 60), (100, 300)];
+    for &(m, period) in periods.iter() {
+        assert_eq!(fibonacci::pisano_period(m), period, "pi({})", m);
+    }
+}
+
+#[test]
+fn iterators_match_fib() {
+    // u64版本在F(93)之后停止
+    let terms: Vec<u64> = fibonacci::iter().collect();
+    assert_eq!(terms.len(), 94);
+    assert!(terms
+        .iter()
+        .zip(0..)
+        .all(|(&f, n)| BigInt::from(f) == fibonacci::fib(n)));
+    assert!(fibonacci::iter_big()
+        .zip(0..200)
+        .all(|(f, n)| BigInt::from(f) == fibonacci::fib(n)));
+}
@@ -0,0 +1,103 @@
+// 用每次只返回几个字节的reader逐块统计，结果要和一次统计整段文本一样
+use rust101::textstats::TextStats;
+use std::io::{self, Read};
+
+const MB: usize = 1 << 20;
+
+// 每次read最多返回size个字节，UTF-8字符、字形簇和单词都会被随意截断
+struct Trickle<'a> {
+    data: &'a [u8],
+    size: usize,
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.size.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+fn trickle(data: &[u8], size: usize) -> TextStats {
+    TextStats::from_reader(Trickle { data, size }).unwrap()
+}
+
+fn whole(text: &str) -> TextStats {
+    let mut stats = TextStats::default();
+    stats.add_str(text);
+    stats
+}
+
+#[test]
+fn one_long_word_without_newline() {
+    let text = "a".repeat(MB);
+    let stats = trickle(text.as_bytes(), 7);
+    assert_eq!(stats.bytes, MB as u64);
+    assert_eq!(stats.chars, MB as u64);
+    assert_eq!(stats.graphemes, MB as u64);
+    assert_eq!(stats.words, 1);
+    assert_eq!(stats.lines, 0);
+    assert_eq!(stats.longest_word, text);
+}
+
+#[test]
+fn mixed_text_without_newline() {
+    // 多字节字符、组合符号、国旗、ZWJ表情、带标点的单词、中文和连续的空白
+    let pieces = [
+        "hello",
+        "naïve",
+        "cafe\u{301}",
+        "ab.cd",
+        "can't",
+        "3.14",
+        "你好世界",
+        "🇨🇳🇺🇸",
+        "👨\u{200d}👩\u{200d}👧",
+        "snake_case",
+        "\u{915}\u{93f}",
+        ",",
+        "。",
+    ];
+    let mut text = String::new();
+    let mut i = 0;
+    while text.len() < MB {
+        text.push_str(pieces[i % pieces.len()]);
+        text.push_str(if i % 5 == 0 { "   " } else { " " });
+        i += 7;
+    }
+    // 中间夹一个很长的单词和一长串空白
+    text.push_str(&"long".repeat(50_000));
+    text.push_str(&" ".repeat(100_000));
+    text.push_str("end");
+
+    let expected = whole(&text);
+    for &size in [1, 7, 4096, 100_000].iter() {
+        assert_eq!(
+            trickle(text.as_bytes(), size),
+            expected,
+            "chunk size {}",
+            size
+        );
+    }
+}
+
+#[test]
+fn invalid_utf8_split_across_reads() {
+    let mut data = Vec::new();
+    for i in 0..20_000 {
+        data.extend_from_slice("wörd 你好 ".as_bytes());
+        if i % 7 == 0 {
+            data.push(0xff);
+        }
+        // 被截断的三字节字符
+        if i % 11 == 0 {
+            data.extend_from_slice(&"好".as_bytes()[..2]);
+        }
+    }
+    let mut expected = whole(&String::from_utf8_lossy(&data));
+    expected.bytes = data.len() as u64;
+    for &size in [1, 2, 3, 64].iter() {
+        assert_eq!(trickle(&data, size), expected, "chunk size {}", size);
+    }
+}
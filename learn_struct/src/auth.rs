@@ -0,0 +1,286 @@
+// 密码登录：密码用加盐的PBKDF2-HMAC-SHA256保存，登录成功才增加登录次数，
+// 连续输错一定次数后锁定账号，锁定可以设置一段时间后自动解除
+use crate::clock::Clock;
+use crate::repository::{RepositoryError, UserId, UserRepository};
+use crate::users::{AccountState, User, UserError};
+use chrono::{DateTime, Duration, Utc};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use std::fmt;
+use std::sync::Arc;
+
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+const ALGORITHM: &str = "pbkdf2-sha256";
+
+// 保存的是盐和派生出来的哈希，不保存密码本身
+// 字符串形式是 pbkdf2-sha256$迭代次数$盐$哈希，盐和哈希用十六进制表示
+#[derive(Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    iterations: u32,
+    salt: [u8; SALT_LEN],
+    hash: [u8; HASH_LEN],
+}
+
+impl PasswordHash {
+    // OWASP推荐PBKDF2-HMAC-SHA256至少迭代600000次
+    pub const DEFAULT_ITERATIONS: u32 = 600_000;
+    // 从文件读取的哈希最多迭代这么多次，否则改过的文件可以让每次登录都算上好几分钟
+    pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+    pub fn new(password: &str) -> PasswordHash {
+        PasswordHash::with_iterations(password, PasswordHash::DEFAULT_ITERATIONS)
+    }
+
+    // 迭代次数越多越难暴力破解，计算也越慢，演示时可以用较小的值
+    pub fn with_iterations(password: &str, iterations: u32) -> PasswordHash {
+        let mut salt = [0; SALT_LEN];
+        getrandom::getrandom(&mut salt).expect("no system random number generator");
+        PasswordHash {
+            iterations,
+            salt,
+            hash: derive(password, &salt, iterations),
+        }
+    }
+
+    // 用保存的盐和迭代次数重新计算，逐字节比较全部的哈希，耗时和哪个字节不同无关
+    pub fn verify(&self, password: &str) -> bool {
+        let hash = derive(password, &self.salt, self.iterations);
+        hash.iter()
+            .zip(self.hash.iter())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    // 不对应任何密码的哈希，用来在用户不存在时也花同样的时间验证一次
+    fn dummy(iterations: u32) -> PasswordHash {
+        PasswordHash {
+            iterations,
+            salt: [0; SALT_LEN],
+            hash: [0; HASH_LEN],
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<PasswordHash, UserError> {
+        let invalid = |reason| UserError::InvalidPasswordHash(reason);
+        let parts: Vec<&str> = input.split('$').collect();
+        if parts.len() != 4 || parts[0] != ALGORITHM {
+            return Err(invalid("expected pbkdf2-sha256$iterations$salt$hash"));
+        }
+        let iterations = match parts[1].parse() {
+            Ok(n) if n > 0 && n <= PasswordHash::MAX_ITERATIONS => n,
+            _ => return Err(invalid("iterations must be between 1 and 10000000")),
+        };
+        let mut salt = [0; SALT_LEN];
+        hex::decode_to_slice(parts[2], &mut salt)
+            .map_err(|_| invalid("salt must be 16 bytes of hex"))?;
+        let mut hash = [0; HASH_LEN];
+        hex::decode_to_slice(parts[3], &mut hash)
+            .map_err(|_| invalid("hash must be 32 bytes of hex"))?;
+        Ok(PasswordHash {
+            iterations,
+            salt,
+            hash,
+        })
+    }
+}
+
+fn derive(password: &str, salt: &[u8], iterations: u32) -> [u8; HASH_LEN] {
+    let mut hash = [0; HASH_LEN];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut hash);
+    hash
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}${}${}${}",
+            ALGORITHM,
+            self.iterations,
+            hex::encode(self.salt),
+            hex::encode(self.hash)
+        )
+    }
+}
+
+// 打印User时不输出盐和哈希
+impl fmt::Debug for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PasswordHash({}, {} iterations)",
+            ALGORITHM, self.iterations
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    // 连续输错这么多次后锁定
+    pub max_failed_attempts: u32,
+    // 锁定多久后自动解除，None表示一直锁定，直到调用unlock
+    pub lockout: Option<Duration>,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> LockoutPolicy {
+        LockoutPolicy {
+            max_failed_attempts: 5,
+            lockout: Some(Duration::minutes(15)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignInResult {
+    Ok(UserId),
+    // 用户不存在时也返回WrongPassword，不透露哪些用户名已经注册
+    WrongPassword,
+    // until是自动解除锁定的时间，None表示需要手动解锁
+    Locked { until: Option<DateTime<Utc>> },
+    // 密码正确，但是账号已经停用
+    Inactive,
+}
+
+impl fmt::Display for SignInResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignInResult::Ok(id) => write!(f, "signed in as user {}", id),
+            SignInResult::WrongPassword => write!(f, "wrong username or password"),
+            SignInResult::Locked { until: Some(until) } => {
+                write!(f, "account is locked until {}", until)
+            }
+            SignInResult::Locked { until: None } => write!(f, "account is locked"),
+            SignInResult::Inactive => write!(f, "account is inactive"),
+        }
+    }
+}
+
+// 根据密码是否正确更新账号，返回登录结果
+// 锁定已经到期的账号先解锁；锁定中的账号不管密码对不对都返回Locked
+pub fn apply_sign_in(
+    id: UserId,
+    user: &mut User,
+    verified: bool,
+    policy: &LockoutPolicy,
+    now: DateTime<Utc>,
+) -> SignInResult {
+    if user.state() == AccountState::Locked {
+        match user.locked_until() {
+            Some(until) if until <= now => user.unlock().unwrap(),
+            until => return SignInResult::Locked { until },
+        }
+    }
+    if !verified {
+        // 停用的账号不会被锁定，也不记录失败次数
+        if user.state() == AccountState::Active
+            && user.record_failed_sign_in() >= policy.max_failed_attempts
+        {
+            let until = policy.lockout.map(|lockout| now + lockout);
+            user.lock_until(until).unwrap();
+            return SignInResult::Locked { until };
+        }
+        return SignInResult::WrongPassword;
+    }
+    match user.record_sign_in(now) {
+        Ok(()) => SignInResult::Ok(id),
+        Err(_) => SignInResult::Inactive,
+    }
+}
+
+// 基于用户仓库的登录服务
+pub struct Authenticator {
+    repo: Arc<dyn UserRepository>,
+    clock: Arc<dyn Clock>,
+    policy: LockoutPolicy,
+    // set_password计算新哈希时用的迭代次数
+    iterations: u32,
+    // 用户不存在或者没有密码时拿来验证的哈希
+    dummy: PasswordHash,
+}
+
+impl Authenticator {
+    pub fn new(repo: Arc<dyn UserRepository>, clock: Arc<dyn Clock>) -> Authenticator {
+        Authenticator {
+            repo,
+            clock,
+            policy: LockoutPolicy::default(),
+            iterations: PasswordHash::DEFAULT_ITERATIONS,
+            dummy: PasswordHash::dummy(PasswordHash::DEFAULT_ITERATIONS),
+        }
+    }
+
+    // set_password保存新密码时使用的迭代次数，默认是DEFAULT_ITERATIONS
+    // 用户不存在时也按这个次数空算一遍，登录失败的耗时不会暴露用户名有没有注册
+    // 已经保存的哈希仍然按各自记录的次数验证
+    pub fn with_iterations(mut self, iterations: u32) -> Authenticator {
+        self.iterations = iterations;
+        self.dummy = PasswordHash::dummy(iterations);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: LockoutPolicy) -> Authenticator {
+        self.policy = policy;
+        self
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    // 哈希在锁外面计算
+    pub fn set_password(&self, id: UserId, password: &str) -> Result<(), RepositoryError> {
+        let hash = PasswordHash::with_iterations(password, self.iterations);
+        self.repo
+            .modify(id, &mut |user| user.set_password(hash.clone()))
+            .map(|_| ())
+    }
+
+    // login可以是用户名或者邮箱
+    // 计算哈希比较慢，所以在锁外面验证密码，只把更新账号的部分放在仓库的写锁里
+    pub fn sign_in(&self, login: &str, password: &str) -> SignInResult {
+        let found = if login.contains('@') {
+            self.repo.find_by_email(login)
+        } else {
+            self.repo.find_by_username(login)
+        };
+        let (id, user) = match found {
+            Some(found) => found,
+            None => {
+                self.dummy.verify(password);
+                return SignInResult::WrongPassword;
+            }
+        };
+        let now = self.clock.now();
+        // 锁定中的账号不验证密码，锁定期间无法继续猜
+        if user.state() == AccountState::Locked {
+            match user.locked_until() {
+                Some(until) if until <= now => {}
+                until => return SignInResult::Locked { until },
+            }
+        }
+        // 没有设置密码的账号不能用密码登录，也不算输错
+        let verified = match user.password() {
+            Some(hash) => hash.verify(password),
+            None => {
+                self.dummy.verify(password);
+                return SignInResult::WrongPassword;
+            }
+        };
+
+        let mut result = SignInResult::WrongPassword;
+        let policy = self.policy;
+        match self.repo.modify(id, &mut |user| {
+            result = apply_sign_in(id, user, verified, &policy, now)
+        }) {
+            Ok(_) => result,
+            // 验证密码时用户被删除了
+            Err(_) => SignInResult::WrongPassword,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+// main里保留讲解结构体的示例代码，这里是在示例基础上扩展出来的用户账号模块
+pub mod auth;
+pub mod clock;
+pub mod repository;
+pub mod sessions;
+pub mod storage;
+pub mod users;
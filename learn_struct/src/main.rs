@@ -1,3 +1,11 @@
+use chrono::{Duration, TimeZone, Utc};
+use learn_struct::auth::{Authenticator, LockoutPolicy, PasswordHash};
+use learn_struct::clock::FakeClock;
+use learn_struct::repository::{InMemoryUserRepository, Page, UserFilter, UserId, UserRepository};
+use learn_struct::sessions::{SessionPolicy, SessionStore};
+use learn_struct::storage::{self, Format};
+use learn_struct::users::{self, AccountState, Email, UserError, Username};
+use std::sync::Arc;
 
 // 定义结构体，注意成员定义和类型
 // 注意这里面的成员都是变量，而不是引用
@@ -52,4 +60,194 @@ fn main() {
     let mut  user = build_user(String::from("xiaoguo"), String::from("dot@dot.com"));
     user.incrSignInCount();
     println!("user name: {}", user.name);
+
+    users_demo();
+    repository_demo();
+    storage_demo();
+    auth_demo();
+    sessions_demo();
+}
+
+// users模块里的User：字段经过校验，状态转换不合法时返回错误
+fn users_demo() {
+    assert!(Email::parse("Dot@Example.COM").is_ok());
+    assert_eq!(
+        Email::parse("Dot@Example.COM").unwrap().as_str(),
+        "Dot@example.com"
+    );
+    for bad in [
+        "dot",
+        "dot@",
+        "@dot.com",
+        "a@b@c.com",
+        "dot@localhost",
+        "d t@dot.com",
+        "dot@-dot.com",
+    ]
+    .iter()
+    {
+        assert!(Email::parse(bad).is_err(), "{}", bad);
+    }
+    assert!(Username::parse("xiaoguo").is_ok());
+    for bad in ["xg", "1xiaoguo", "xiao guo", "小郭abc"].iter() {
+        assert!(Username::parse(bad).is_err(), "{}", bad);
+    }
+
+    match users::User::builder()
+        .username("xiaoguo")
+        .email("dot@dot")
+        .build()
+    {
+        Err(e) => println!("build error: {}", e),
+        Ok(_) => unreachable!(),
+    }
+    assert_eq!(
+        users::User::builder().email("dot@dot.com").build(),
+        Err(UserError::MissingField("username"))
+    );
+
+    let created = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    let mut user = users::User::builder()
+        .username("xiaoguo")
+        .email("dot@dot.com")
+        .created_at(created)
+        .build()
+        .unwrap();
+    assert_eq!(user.created_at(), created);
+    assert_eq!(user.last_sign_in(), None);
+
+    let now = Utc::now();
+    user.record_sign_in(now).unwrap();
+    assert_eq!(user.sign_in_count(), 1);
+    assert_eq!(user.last_sign_in(), Some(now));
+
+    // Active -> Locked -> Active -> Inactive -> Active
+    user.lock().unwrap();
+    println!("{}", user.deactivate().unwrap_err());
+    println!("{}", user.record_sign_in(now).unwrap_err());
+    user.unlock().unwrap();
+    user.deactivate().unwrap();
+    println!("{}", user.lock().unwrap_err());
+    user.activate().unwrap();
+    println!("{}", user.activate().unwrap_err());
+    assert_eq!(user.state(), AccountState::Active);
+    println!("{:?}", user);
+}
+
+fn new_user(username: &str, email: &str) -> users::User {
+    users::User::builder()
+        .username(username)
+        .email(email)
+        .build()
+        .unwrap()
+}
+
+// 用户仓库：增删改查、唯一性约束、过滤分页，完整的检查在tests/repository.rs
+fn repository_demo() {
+    let repo = InMemoryUserRepository::new();
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+    println!("{} -> {}", id, repo.get(id).unwrap().username());
+
+    // 邮箱和用户名不区分大小写
+    println!("{}", repo.create(new_user("other", "DOT@dot.com")).unwrap_err());
+    println!("{}", repo.create(new_user("XiaoGuo", "other@dot.com")).unwrap_err());
+
+    for i in 0..10 {
+        let mut user = new_user(&format!("user{}", i), &format!("user{}@dot.com", i));
+        for _ in 0..i % 3 {
+            user.record_sign_in(Utc::now()).unwrap();
+        }
+        repo.create(user).unwrap();
+    }
+    let filter = UserFilter::default().active(true).min_sign_ins(1);
+    let page = repo.list(&filter, Page::new(1, 3));
+    println!(
+        "{} users match, page 1: {:?}",
+        page.total,
+        page.items.iter().map(|(id, _)| *id).collect::<Vec<UserId>>()
+    );
+}
+
+// 保存和读取用户：两种格式来回转换，损坏的文件报告出错的位置，完整的检查在tests/storage.rs
+fn storage_demo() {
+    let repo = InMemoryUserRepository::new();
+    for i in 0..4 {
+        let mut user = new_user(&format!("user{}", i), &format!("User{}@Dot.com", i));
+        if i == 3 {
+            user.lock().unwrap();
+        }
+        repo.create(user).unwrap();
+    }
+    let users = repo.list(&UserFilter::default(), Page::all()).items;
+
+    for &format in [Format::JsonLines, Format::Binary].iter() {
+        let mut buf = Vec::new();
+        storage::save(&mut buf, format, &users).unwrap();
+        println!("{:?}: {} users in {} bytes", format, users.len(), buf.len());
+        // 截断的文件
+        let err = storage::load(&buf[..buf.len() - 3], format).unwrap_err();
+        println!("  {}", err);
+    }
+
+    // 版本1的文件：name和active，没有时间
+    let v1 = r#"{"format":"learn_struct.users","version":1}
+{"id":7,"name":"retired","email":"old@dot.com","active":false,"sign_in_count":0}
+"#;
+    let migrated = storage::load(v1.as_bytes(), Format::JsonLines).unwrap();
+    println!("{} {:?}", migrated[0].0, migrated[0].1);
+    let newer = r#"{"format":"learn_struct.users","version":9}"#;
+    println!(
+        "{}",
+        storage::load(newer.as_bytes(), Format::JsonLines).unwrap_err()
+    );
+}
+
+// 密码登录：时间由FakeClock控制，可以直接跳过锁定的时间，完整的检查在tests/auth.rs
+fn auth_demo() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    let clock = Arc::new(FakeClock::new(start));
+    let repo = Arc::new(InMemoryUserRepository::new());
+    // 演示时用较少的迭代次数，不然debug版本每次计算哈希都要等很久
+    let auth = Authenticator::new(repo.clone(), clock.clone())
+        .with_iterations(1000)
+        .with_policy(LockoutPolicy {
+            max_failed_attempts: 3,
+            lockout: Some(Duration::minutes(10)),
+        });
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+    auth.set_password(id, "correct horse").unwrap();
+    println!("{:?}", repo.get(id).unwrap().password().unwrap());
+    println!("{}", PasswordHash::parse("md5$abc").unwrap_err());
+
+    println!("{}", auth.sign_in("DOT@dot.com", "correct horse"));
+    // 连续输错3次后锁定10分钟，到时间后自动解锁
+    for _ in 0..3 {
+        println!("{}", auth.sign_in("xiaoguo", "wrong"));
+    }
+    clock.advance(Duration::minutes(10));
+    println!("{}", auth.sign_in("xiaoguo", "correct horse"));
+}
+
+// 登录之后的会话：令牌的验证和过期，完整的检查在tests/sessions.rs
+fn sessions_demo() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    let clock = Arc::new(FakeClock::new(start));
+    let repo = Arc::new(InMemoryUserRepository::new());
+    let sessions = SessionStore::new(repo.clone(), clock.clone()).with_policy(SessionPolicy {
+        absolute: Duration::hours(8),
+        idle: Duration::minutes(30),
+    });
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+
+    let laptop = sessions.create(id).unwrap();
+    println!("{:?} -> {:?}", laptop, sessions.validate(laptop.as_str()));
+    // 30分钟不用就过期
+    clock.advance(Duration::minutes(30));
+    println!("{}", sessions.validate(laptop.as_str()).unwrap_err());
+
+    // 停用账号后已经发出的令牌马上失效
+    let phone = sessions.create(id).unwrap();
+    repo.modify(id, &mut |user| user.deactivate().unwrap())
+        .unwrap();
+    println!("{}", sessions.validate(phone.as_str()).unwrap_err());
 }
@@ -0,0 +1,311 @@
+// 用户的存储：UserRepository定义增删改查和按条件分页查询，InMemoryUserRepository是放在内存里的实现
+// 实现要求是Send + Sync，多个线程可以通过Arc<dyn UserRepository>共享同一个仓库
+use crate::users::User;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::RwLock;
+
+// 由仓库分配的id，User本身不带id，同一个User可以先构造好再交给仓库保存
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UserId(pub u64);
+
+impl fmt::Display for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepositoryError {
+    NotFound(UserId),
+    // 邮箱或者用户名已经被另一个用户使用，带上占用者的id
+    DuplicateEmail { email: String, owner: UserId },
+    DuplicateUsername { username: String, owner: UserId },
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepositoryError::NotFound(id) => write!(f, "user {} not found", id),
+            RepositoryError::DuplicateEmail { email, owner } => {
+                write!(f, "email {} is already used by user {}", email, owner)
+            }
+            RepositoryError::DuplicateUsername { username, owner } => {
+                write!(f, "username {} is already used by user {}", username, owner)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+// 查询条件，没有设置的条件不做过滤
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserFilter {
+    pub active: Option<bool>,
+    pub min_sign_ins: Option<u64>,
+}
+
+impl UserFilter {
+    pub fn active(mut self, active: bool) -> UserFilter {
+        self.active = Some(active);
+        self
+    }
+
+    pub fn min_sign_ins(mut self, count: u64) -> UserFilter {
+        self.min_sign_ins = Some(count);
+        self
+    }
+
+    pub fn matches(&self, user: &User) -> bool {
+        if let Some(active) = self.active {
+            if user.is_active() != active {
+                return false;
+            }
+        }
+        if let Some(count) = self.min_sign_ins {
+            if user.sign_in_count() < count {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// 分页参数，跳过前offset个结果后最多返回limit个
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Page {
+    // 第number页（从0开始），每页size个
+    pub fn new(number: usize, size: usize) -> Page {
+        Page {
+            offset: number.saturating_mul(size),
+            limit: size,
+        }
+    }
+
+    pub fn all() -> Page {
+        Page {
+            offset: 0,
+            limit: usize::MAX,
+        }
+    }
+}
+
+// 一页查询结果，total是满足条件的总数，用来计算一共有多少页
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageResult {
+    pub items: Vec<(UserId, User)>,
+    pub total: usize,
+}
+
+pub trait UserRepository: Send + Sync {
+    // 保存新用户并返回分配的id，邮箱或者用户名重复时返回错误
+    fn create(&self, user: User) -> Result<UserId, RepositoryError>;
+
+    fn get(&self, id: UserId) -> Option<User>;
+
+    // 按用户名或者邮箱查找，和唯一性检查一样不区分大小写
+    fn find_by_username(&self, username: &str) -> Option<(UserId, User)>;
+
+    fn find_by_email(&self, email: &str) -> Option<(UserId, User)>;
+
+    // 整个替换已有的用户，改了邮箱或者用户名时同样检查唯一性
+    fn update(&self, id: UserId, user: User) -> Result<(), RepositoryError>;
+
+    // 读取、修改、写回在一次操作里完成，中间不会插入其他线程的修改，返回修改后的用户
+    // f可能被调用后因为唯一性检查失败而不保存修改
+    fn modify(&self, id: UserId, f: &mut dyn FnMut(&mut User)) -> Result<User, RepositoryError>;
+
+    // 删除并返回被删除的用户
+    fn delete(&self, id: UserId) -> Result<User, RepositoryError>;
+
+    // 按id从小到大返回满足条件的一页
+    fn list(&self, filter: &UserFilter, page: Page) -> PageResult;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// 邮箱和用户名都不区分大小写地判断是否重复，避免Dot@dot.com和dot@dot.com注册成两个账号
+fn email_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn username_key(username: &str) -> String {
+    username.trim().to_ascii_lowercase()
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_id: u64,
+    users: BTreeMap<UserId, User>,
+    by_email: HashMap<String, UserId>,
+    by_username: HashMap<String, UserId>,
+}
+
+impl Inner {
+    // 检查user的邮箱和用户名有没有被别人占用，更新时自己占用的不算
+    fn check_unique(&self, user: &User, exclude: Option<UserId>) -> Result<(), RepositoryError> {
+        if let Some(&owner) = self.by_email.get(&email_key(user.email().as_str())) {
+            if Some(owner) != exclude {
+                return Err(RepositoryError::DuplicateEmail {
+                    email: user.email().to_string(),
+                    owner,
+                });
+            }
+        }
+        if let Some(&owner) = self
+            .by_username
+            .get(&username_key(user.username().as_str()))
+        {
+            if Some(owner) != exclude {
+                return Err(RepositoryError::DuplicateUsername {
+                    username: user.username().to_string(),
+                    owner,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn find(&self, id: Option<&UserId>) -> Option<(UserId, User)> {
+        let id = *id?;
+        self.users.get(&id).map(|user| (id, user.clone()))
+    }
+
+    // 用修改后的user替换id对应的用户，调用前要确认id存在
+    fn replace(&mut self, id: UserId, user: User) -> Result<(), RepositoryError> {
+        self.check_unique(&user, Some(id))?;
+        let old = self.users.insert(id, user.clone()).unwrap();
+        self.unindex(&old);
+        self.index(id, &user);
+        Ok(())
+    }
+
+    fn index(&mut self, id: UserId, user: &User) {
+        self.by_email.insert(email_key(user.email().as_str()), id);
+        self.by_username
+            .insert(username_key(user.username().as_str()), id);
+    }
+
+    fn unindex(&mut self, user: &User) {
+        self.by_email.remove(&email_key(user.email().as_str()));
+        self.by_username
+            .remove(&username_key(user.username().as_str()));
+    }
+}
+
+// 所有数据放在一个RwLock里，检查唯一性和插入在同一次写锁里完成，
+// 所以并发插入同一个邮箱时只有一个能成功
+#[derive(Debug, Default)]
+pub struct InMemoryUserRepository {
+    inner: RwLock<Inner>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> InMemoryUserRepository {
+        InMemoryUserRepository::default()
+    }
+
+    // 用已有id的用户构造仓库，比如从文件读取的用户，新用户的id从最大的id之后开始分配
+    // id重复时后面的覆盖前面的，邮箱或者用户名重复时返回错误
+    pub fn from_users<I>(users: I) -> Result<InMemoryUserRepository, RepositoryError>
+    where
+        I: IntoIterator<Item = (UserId, User)>,
+    {
+        let mut inner = Inner::default();
+        for (id, user) in users {
+            if let Some(old) = inner.users.remove(&id) {
+                inner.unindex(&old);
+            }
+            inner.check_unique(&user, None)?;
+            inner.index(id, &user);
+            inner.users.insert(id, user);
+            inner.next_id = inner.next_id.max(id.0);
+        }
+        Ok(InMemoryUserRepository {
+            inner: RwLock::new(inner),
+        })
+    }
+}
+
+impl UserRepository for InMemoryUserRepository {
+    fn create(&self, user: User) -> Result<UserId, RepositoryError> {
+        let mut inner = self.inner.write().unwrap();
+        inner.check_unique(&user, None)?;
+        inner.next_id += 1;
+        let id = UserId(inner.next_id);
+        inner.index(id, &user);
+        inner.users.insert(id, user);
+        Ok(id)
+    }
+
+    fn get(&self, id: UserId) -> Option<User> {
+        self.inner.read().unwrap().users.get(&id).cloned()
+    }
+
+    fn find_by_username(&self, username: &str) -> Option<(UserId, User)> {
+        let inner = self.inner.read().unwrap();
+        inner.find(inner.by_username.get(&username_key(username)))
+    }
+
+    fn find_by_email(&self, email: &str) -> Option<(UserId, User)> {
+        let inner = self.inner.read().unwrap();
+        inner.find(inner.by_email.get(&email_key(email)))
+    }
+
+    fn update(&self, id: UserId, user: User) -> Result<(), RepositoryError> {
+        let mut inner = self.inner.write().unwrap();
+        if !inner.users.contains_key(&id) {
+            return Err(RepositoryError::NotFound(id));
+        }
+        inner.replace(id, user)
+    }
+
+    fn modify(&self, id: UserId, f: &mut dyn FnMut(&mut User)) -> Result<User, RepositoryError> {
+        let mut inner = self.inner.write().unwrap();
+        let mut user = inner
+            .users
+            .get(&id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound(id))?;
+        f(&mut user);
+        inner.replace(id, user.clone())?;
+        Ok(user)
+    }
+
+    fn delete(&self, id: UserId) -> Result<User, RepositoryError> {
+        let mut inner = self.inner.write().unwrap();
+        let user = inner
+            .users
+            .remove(&id)
+            .ok_or(RepositoryError::NotFound(id))?;
+        inner.unindex(&user);
+        Ok(user)
+    }
+
+    fn list(&self, filter: &UserFilter, page: Page) -> PageResult {
+        let inner = self.inner.read().unwrap();
+        let mut total = 0;
+        let mut items = Vec::new();
+        for (&id, user) in inner.users.iter().filter(|(_, user)| filter.matches(user)) {
+            if total >= page.offset && items.len() < page.limit {
+                items.push((id, user.clone()));
+            }
+            total += 1;
+        }
+        PageResult { items, total }
+    }
+
+    fn len(&self) -> usize {
+        self.inner.read().unwrap().users.len()
+    }
+}
@@ -0,0 +1,320 @@
+// 登录成功后的会话：给客户端一个随机的令牌，之后的请求用令牌换回用户id
+// 会话有两种过期：从创建开始算的绝对过期，和从最后一次使用开始算的空闲过期，先到的那个生效
+// 存储里只保存令牌的SHA-256，就算内存里的数据泄露也拿不到可以直接使用的令牌
+// 会话跟着账号的状态走，账号被停用、锁定或者删除后，它的令牌都不能再用
+use crate::clock::Clock;
+use crate::repository::{UserId, UserRepository};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+const TOKEN_LEN: usize = 32;
+
+// 交给客户端的令牌，32字节随机数的十六进制，除此之外不包含任何信息
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionToken(String);
+
+impl SessionToken {
+    fn generate() -> SessionToken {
+        let mut bytes = [0; TOKEN_LEN];
+        getrandom::getrandom(&mut bytes).expect("no system random number generator");
+        SessionToken(hex::encode(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SessionToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// 打印日志时只输出开头几位
+impl fmt::Debug for SessionToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SessionToken({}...)", &self.0[..8])
+    }
+}
+
+type TokenHash = [u8; 32];
+
+fn hash_token(token: &str) -> TokenHash {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+// 会话的编号，可以显示给用户，用来退出某一个设备上的登录，不能代替令牌使用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionId(pub u64);
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "session {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionPolicy {
+    // 创建后最多能用多久
+    pub absolute: Duration,
+    // 多久不用就过期，每次验证令牌都会重新计时
+    pub idle: Duration,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> SessionPolicy {
+        SessionPolicy {
+            absolute: Duration::days(7),
+            idle: Duration::minutes(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: SessionId,
+    pub user_id: UserId,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn expires_at(&self, policy: &SessionPolicy) -> DateTime<Utc> {
+        (self.created_at + policy.absolute).min(self.last_seen + policy.idle)
+    }
+
+    pub fn is_expired(&self, policy: &SessionPolicy, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at(policy)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    // 令牌不存在，或者已经被撤销、清理
+    Unknown,
+    // 令牌存在但是已经过期，同时会被删除
+    Expired { at: DateTime<Utc> },
+    // 账号不存在或者不是Active状态，这个用户所有的会话同时会被删除
+    Inactive(UserId),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Unknown => write!(f, "unknown session token"),
+            SessionError::Expired { at } => write!(f, "session expired at {}", at),
+            SessionError::Inactive(id) => write!(f, "user {} is not active", id),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_id: u64,
+    sessions: HashMap<TokenHash, Session>,
+}
+
+pub struct SessionStore {
+    inner: Mutex<Inner>,
+    users: Arc<dyn UserRepository>,
+    clock: Arc<dyn Clock>,
+    policy: SessionPolicy,
+}
+
+impl SessionStore {
+    // users用来在创建和验证会话时检查账号的状态
+    pub fn new(users: Arc<dyn UserRepository>, clock: Arc<dyn Clock>) -> SessionStore {
+        SessionStore {
+            inner: Mutex::new(Inner::default()),
+            users,
+            clock,
+            policy: SessionPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: SessionPolicy) -> SessionStore {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &SessionPolicy {
+        &self.policy
+    }
+
+    fn is_active(&self, user_id: UserId) -> bool {
+        self.users.get(user_id).is_some_and(|user| user.is_active())
+    }
+
+    // 为登录成功的用户创建会话，返回的令牌只有这一次机会拿到
+    pub fn create(&self, user_id: UserId) -> Result<SessionToken, SessionError> {
+        if !self.is_active(user_id) {
+            return Err(SessionError::Inactive(user_id));
+        }
+        let token = SessionToken::generate();
+        let now = self.clock.now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let session = Session {
+            id: SessionId(inner.next_id),
+            user_id,
+            created_at: now,
+            last_seen: now,
+        };
+        inner.sessions.insert(hash_token(token.as_str()), session);
+        Ok(token)
+    }
+
+    // 验证令牌并刷新空闲时间，返回刷新后的会话
+    // 每次都检查账号的状态，停用或者锁定账号的代码不需要记得去撤销会话
+    pub fn validate(&self, token: &str) -> Result<Session, SessionError> {
+        let now = self.clock.now();
+        let key = hash_token(token);
+        let mut inner = self.inner.lock().unwrap();
+        let session = inner.sessions.get_mut(&key).ok_or(SessionError::Unknown)?;
+        if session.is_expired(&self.policy, now) {
+            let at = session.expires_at(&self.policy);
+            inner.sessions.remove(&key);
+            return Err(SessionError::Expired { at });
+        }
+        let user_id = session.user_id;
+        if !self.is_active(user_id) {
+            inner
+                .sessions
+                .retain(|_, session| session.user_id != user_id);
+            return Err(SessionError::Inactive(user_id));
+        }
+        let session = inner.sessions.get_mut(&key).unwrap();
+        session.last_seen = now;
+        Ok(session.clone())
+    }
+
+    // 退出登录，令牌不存在时返回false
+    pub fn revoke(&self, token: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.sessions.remove(&hash_token(token)).is_some()
+    }
+
+    // 按编号撤销，只能撤销属于user_id的会话
+    pub fn revoke_id(&self, user_id: UserId, id: SessionId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.sessions.len();
+        inner
+            .sessions
+            .retain(|_, session| session.id != id || session.user_id != user_id);
+        inner.sessions.len() < before
+    }
+
+    // 撤销用户所有的会话，比如修改密码之后，返回撤销的个数
+    pub fn revoke_all(&self, user_id: UserId) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.sessions.len();
+        inner
+            .sessions
+            .retain(|_, session| session.user_id != user_id);
+        before - inner.sessions.len()
+    }
+
+    // 用户还没有过期的会话，按创建的先后排序
+    pub fn list(&self, user_id: UserId) -> Vec<Session> {
+        let now = self.clock.now();
+        let inner = self.inner.lock().unwrap();
+        let mut sessions: Vec<Session> = inner
+            .sessions
+            .values()
+            .filter(|session| session.user_id == user_id && !session.is_expired(&self.policy, now))
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|session| session.id);
+        sessions
+    }
+
+    // 删除所有过期的会话，返回删除的个数
+    pub fn purge_expired(&self) -> usize {
+        let now = self.clock.now();
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.sessions.len();
+        let policy = self.policy;
+        inner
+            .sessions
+            .retain(|_, session| !session.is_expired(&policy, now));
+        before - inner.sessions.len()
+    }
+
+    // 包括已经过期但是还没有被清理的会话
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// 后台定期清理过期会话的线程，调用stop或者drop时结束
+pub struct Cleanup {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Cleanup {
+    // 每隔interval（真实的时间）调用一次purge_expired，是否过期仍然按store的时钟判断
+    pub fn spawn(store: Arc<SessionStore>, interval: std::time::Duration) -> Cleanup {
+        Cleanup::start(store, interval, None)
+    }
+
+    // 每清理一遍就把删除的个数发给report，调用方可以等到清理真正运行过再检查结果
+    pub fn spawn_with_report(
+        store: Arc<SessionStore>,
+        interval: std::time::Duration,
+        report: Sender<usize>,
+    ) -> Cleanup {
+        Cleanup::start(store, interval, Some(report))
+    }
+
+    fn start(
+        store: Arc<SessionStore>,
+        interval: std::time::Duration,
+        report: Option<Sender<usize>>,
+    ) -> Cleanup {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || loop {
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {
+                    let purged = store.purge_expired();
+                    // 接收端不在了也继续清理
+                    if let Some(report) = &report {
+                        let _ = report.send(purged);
+                    }
+                }
+                // 收到消息或者Sender被丢弃都表示要停止
+                _ => return,
+            }
+        });
+        Cleanup {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    // 通知线程停止并等待它结束
+    pub fn stop(self) {
+        drop(self)
+    }
+}
+
+impl Drop for Cleanup {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,564 @@
+// 把用户保存到文件以及从文件读取，支持两种格式：
+// JSON Lines：第一行是文件头，之后每行一个用户，方便查看和用其他工具处理
+// 二进制：魔数和版本号开头，之后是小端整数和带长度前缀的字符串，文件更小
+// 保存总是写当前版本；读取旧版本的文件时，每条记录先迁移成当前版本再构造User，
+// 构造时同样会校验邮箱和用户名
+use crate::auth::PasswordHash;
+use crate::repository::UserId;
+use crate::users::{AccountState, User, UserError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// 每次记录的字段有变化都要加一个版本，并且写好从上一个版本迁移的代码
+// 1: 最早的User，只有用户名、邮箱、是否启用和登录次数
+// 2: 用账号状态代替是否启用，加上创建时间和最近登录时间
+// 3: 加上密码哈希、连续输错密码的次数和自动解锁的时间
+pub const CURRENT_VERSION: u16 = 3;
+
+// v1的记录没有创建时间，迁移时用Unix纪元表示未知
+pub const LEGACY_CREATED_AT: DateTime<Utc> = DateTime::<Utc>::UNIX_EPOCH;
+
+const FORMAT_NAME: &str = "learn_struct.users";
+const MAGIC: &[u8; 4] = b"USRS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    JsonLines,
+    Binary,
+}
+
+impl Format {
+    // 按扩展名判断格式，.jsonl是JSON Lines，.bin是二进制
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "jsonl" => Some(Format::JsonLines),
+            "bin" => Some(Format::Binary),
+            _ => None,
+        }
+    }
+}
+
+// 出错的位置，JSON Lines用行号（从1开始），二进制用字节偏移（从0开始）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Line(usize),
+    Offset(u64),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Location::Line(line) => write!(f, "line {}", line),
+            Location::Offset(offset) => write!(f, "byte offset {}", offset),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    // 文件头的版本号比程序支持的新，或者是0
+    UnsupportedVersion(u16),
+    // 文件内容不符合格式，比如JSON语法错误、缺少字段或者文件被截断
+    Corrupt { at: Location, reason: String },
+    // 格式正确，但是内容不能构造User，比如邮箱不合法
+    InvalidUser { at: Location, error: UserError },
+    DuplicateId { at: Location, id: UserId },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "{}", e),
+            StorageError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported file version {} (this program reads versions 1 to {})",
+                version, CURRENT_VERSION
+            ),
+            StorageError::Corrupt { at, reason } => {
+                write!(f, "corrupted file at {}: {}", at, reason)
+            }
+            StorageError::InvalidUser { at, error } => {
+                write!(f, "invalid user at {}: {}", at, error)
+            }
+            StorageError::DuplicateId { at, id } => write!(f, "duplicate user id {} at {}", id, at),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Io(e) => Some(e),
+            StorageError::InvalidUser { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> StorageError {
+        StorageError::Io(e)
+    }
+}
+
+fn corrupt<S: Into<String>>(at: Location, reason: S) -> StorageError {
+    StorageError::Corrupt {
+        at,
+        reason: reason.into(),
+    }
+}
+
+// 各个版本的记录，字段和当时的User一致，旧版本的记录只用来读取
+#[derive(Debug, Deserialize)]
+struct RecordV1 {
+    id: u64,
+    name: String,
+    email: String,
+    active: bool,
+    sign_in_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordV2 {
+    id: u64,
+    username: String,
+    email: String,
+    state: AccountState,
+    sign_in_count: u64,
+    created_at: DateTime<Utc>,
+    last_sign_in: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordV3 {
+    id: u64,
+    username: String,
+    email: String,
+    state: AccountState,
+    sign_in_count: u64,
+    created_at: DateTime<Utc>,
+    last_sign_in: Option<DateTime<Utc>>,
+    password: Option<String>,
+    failed_sign_ins: u32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+// 当前版本的记录，加版本时改成新的结构体，再给上一个版本加一个From
+type Record = RecordV3;
+
+impl From<RecordV1> for RecordV2 {
+    fn from(old: RecordV1) -> RecordV2 {
+        RecordV2 {
+            id: old.id,
+            username: old.name,
+            email: old.email,
+            state: if old.active {
+                AccountState::Active
+            } else {
+                AccountState::Inactive
+            },
+            sign_in_count: old.sign_in_count,
+            created_at: LEGACY_CREATED_AT,
+            last_sign_in: None,
+        }
+    }
+}
+
+// v2的账号都还没有设置密码
+impl From<RecordV2> for RecordV3 {
+    fn from(old: RecordV2) -> RecordV3 {
+        RecordV3 {
+            id: old.id,
+            username: old.username,
+            email: old.email,
+            state: old.state,
+            sign_in_count: old.sign_in_count,
+            created_at: old.created_at,
+            last_sign_in: old.last_sign_in,
+            password: None,
+            failed_sign_ins: 0,
+            locked_until: None,
+        }
+    }
+}
+
+impl Record {
+    fn from_user(id: UserId, user: &User) -> Record {
+        RecordV3 {
+            id: id.0,
+            username: user.username().to_string(),
+            email: user.email().to_string(),
+            state: user.state(),
+            sign_in_count: user.sign_in_count(),
+            created_at: user.created_at(),
+            last_sign_in: user.last_sign_in(),
+            password: user.password().map(|hash| hash.to_string()),
+            failed_sign_ins: user.failed_sign_ins(),
+            locked_until: user.locked_until(),
+        }
+    }
+
+    fn into_user(self) -> Result<(UserId, User), UserError> {
+        let mut builder = User::builder()
+            .username(self.username)
+            .email(self.email)
+            .state(self.state)
+            .sign_in_count(self.sign_in_count)
+            .created_at(self.created_at)
+            .last_sign_in(self.last_sign_in)
+            .failed_sign_ins(self.failed_sign_ins)
+            .locked_until(self.locked_until);
+        if let Some(password) = self.password {
+            builder = builder.password(PasswordHash::parse(&password)?);
+        }
+        Ok((UserId(self.id), builder.build()?))
+    }
+}
+
+// 把读到的记录转成User，检查id有没有重复
+#[derive(Default)]
+struct Loaded {
+    users: Vec<(UserId, User)>,
+    ids: HashSet<UserId>,
+}
+
+impl Loaded {
+    fn push(&mut self, record: Record, at: Location) -> Result<(), StorageError> {
+        let (id, user) = record
+            .into_user()
+            .map_err(|error| StorageError::InvalidUser { at, error })?;
+        if !self.ids.insert(id) {
+            return Err(StorageError::DuplicateId { at, id });
+        }
+        self.users.push((id, user));
+        Ok(())
+    }
+}
+
+pub fn save<W: Write>(writer: W, format: Format, users: &[(UserId, User)]) -> io::Result<()> {
+    match format {
+        Format::JsonLines => write_json_lines(writer, users),
+        Format::Binary => write_binary(writer, users),
+    }
+}
+
+pub fn load<R: BufRead>(reader: R, format: Format) -> Result<Vec<(UserId, User)>, StorageError> {
+    match format {
+        Format::JsonLines => read_json_lines(reader),
+        Format::Binary => read_binary(reader),
+    }
+}
+
+// 先写到同一个目录下的临时文件再改名，写到一半失败时不会破坏原来的文件
+// 写入或者改名失败时删掉临时文件
+pub fn save_file(path: &Path, format: Format, users: &[(UserId, User)]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let file = File::create(&tmp)?;
+    let result = write_file(file, format, users).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_file(file: File, format: Format, users: &[(UserId, User)]) -> io::Result<()> {
+    let mut writer = BufWriter::new(file);
+    save(&mut writer, format, users)?;
+    writer.into_inner()?.sync_all()
+}
+
+pub fn load_file(path: &Path, format: Format) -> Result<Vec<(UserId, User)>, StorageError> {
+    load(BufReader::new(File::open(path)?), format)
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u16,
+}
+
+fn check_version(version: u16) -> Result<u16, StorageError> {
+    if version == 0 || version > CURRENT_VERSION {
+        return Err(StorageError::UnsupportedVersion(version));
+    }
+    Ok(version)
+}
+
+fn write_json_lines<W: Write>(mut writer: W, users: &[(UserId, User)]) -> io::Result<()> {
+    let header = Header {
+        format: FORMAT_NAME.to_string(),
+        version: CURRENT_VERSION,
+    };
+    serde_json::to_writer(&mut writer, &header)?;
+    writer.write_all(b"\n")?;
+    for (id, user) in users {
+        serde_json::to_writer(&mut writer, &Record::from_user(*id, user))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+fn read_json_lines<R: BufRead>(reader: R) -> Result<Vec<(UserId, User)>, StorageError> {
+    let mut lines = reader.lines().enumerate().map(|(i, line)| {
+        let at = Location::Line(i + 1);
+        match line {
+            Ok(line) => Ok((at, line)),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Err(corrupt(at, "not valid UTF-8")),
+            Err(e) => Err(StorageError::Io(e)),
+        }
+    });
+
+    let (at, line) = match lines.next() {
+        Some(line) => line?,
+        None => return Err(corrupt(Location::Line(1), "empty file, missing header")),
+    };
+    let header: Header =
+        serde_json::from_str(&line).map_err(|e| corrupt(at, format!("invalid header: {}", e)))?;
+    if header.format != FORMAT_NAME {
+        return Err(corrupt(at, format!("not a users file: {}", header.format)));
+    }
+    let version = check_version(header.version)?;
+
+    let mut loaded = Loaded::default();
+    for line in lines {
+        let (at, line) = line?;
+        // 允许空行，比如手动编辑时在末尾多加的换行
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = match version {
+            1 => serde_json::from_str::<RecordV1>(&line).map(|v1| RecordV2::from(v1).into()),
+            2 => serde_json::from_str::<RecordV2>(&line).map(Record::from),
+            _ => serde_json::from_str::<RecordV3>(&line),
+        };
+        let record = record.map_err(|e| corrupt(at, e.to_string()))?;
+        loaded.push(record, at)?;
+    }
+    Ok(loaded.users)
+}
+
+// 二进制格式，所有整数都是小端：
+// 文件头：魔数USRS，版本号u16
+// v1记录：id u64，用户名，邮箱，是否启用u8，登录次数u64
+// v2记录：id u64，用户名，邮箱，状态u8，登录次数u64，创建时间，最近登录时间
+// v3记录：v2的字段，之后是密码哈希（可选的字符串），连续输错次数u32，自动解锁时间
+// 字符串是u16长度加UTF-8字节，时间是秒数i64加纳秒u32，
+// 可选的值先是一个标记u8，为1时后面跟着值
+fn write_binary<W: Write>(mut writer: W, users: &[(UserId, User)]) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&CURRENT_VERSION.to_le_bytes())?;
+    for (id, user) in users {
+        let record = Record::from_user(*id, user);
+        writer.write_all(&record.id.to_le_bytes())?;
+        write_str(&mut writer, &record.username)?;
+        write_str(&mut writer, &record.email)?;
+        writer.write_all(&[state_to_byte(record.state)])?;
+        writer.write_all(&record.sign_in_count.to_le_bytes())?;
+        write_time(&mut writer, record.created_at)?;
+        write_optional_time(&mut writer, record.last_sign_in)?;
+        match &record.password {
+            Some(password) => {
+                writer.write_all(&[1])?;
+                write_str(&mut writer, password)?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+        writer.write_all(&record.failed_sign_ins.to_le_bytes())?;
+        write_optional_time(&mut writer, record.locked_until)?;
+    }
+    writer.flush()
+}
+
+fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    if s.len() > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("string too long for the binary format: {} bytes", s.len()),
+        ));
+    }
+    writer.write_all(&(s.len() as u16).to_le_bytes())?;
+    writer.write_all(s.as_bytes())
+}
+
+fn write_time<W: Write>(writer: &mut W, at: DateTime<Utc>) -> io::Result<()> {
+    writer.write_all(&at.timestamp().to_le_bytes())?;
+    writer.write_all(&at.timestamp_subsec_nanos().to_le_bytes())
+}
+
+fn write_optional_time<W: Write>(writer: &mut W, at: Option<DateTime<Utc>>) -> io::Result<()> {
+    match at {
+        Some(at) => {
+            writer.write_all(&[1])?;
+            write_time(writer, at)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn state_to_byte(state: AccountState) -> u8 {
+    match state {
+        AccountState::Active => 0,
+        AccountState::Inactive => 1,
+        AccountState::Locked => 2,
+    }
+}
+
+// 记录读到了哪个字节，出错时报告出错字段的偏移
+struct BinaryReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R: BufRead> BinaryReader<R> {
+    fn at_end(&mut self) -> Result<bool, StorageError> {
+        Ok(self.inner.fill_buf()?.is_empty())
+    }
+
+    fn bytes(&mut self, len: usize, what: &str) -> Result<Vec<u8>, StorageError> {
+        let mut buf = Vec::with_capacity(len);
+        let read = (&mut self.inner).take(len as u64).read_to_end(&mut buf)?;
+        if read < len {
+            return Err(corrupt(
+                Location::Offset(self.offset),
+                format!("file ends in the middle of {}", what),
+            ));
+        }
+        self.offset += len as u64;
+        Ok(buf)
+    }
+
+    fn array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], StorageError> {
+        let mut array = [0; N];
+        array.copy_from_slice(&self.bytes(N, what)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self, what: &str) -> Result<u8, StorageError> {
+        Ok(self.array::<1>(what)?[0])
+    }
+
+    fn u16(&mut self, what: &str) -> Result<u16, StorageError> {
+        Ok(u16::from_le_bytes(self.array(what)?))
+    }
+
+    fn u32(&mut self, what: &str) -> Result<u32, StorageError> {
+        Ok(u32::from_le_bytes(self.array(what)?))
+    }
+
+    fn u64(&mut self, what: &str) -> Result<u64, StorageError> {
+        Ok(u64::from_le_bytes(self.array(what)?))
+    }
+
+    fn bool(&mut self, what: &str) -> Result<bool, StorageError> {
+        let at = Location::Offset(self.offset);
+        match self.u8(what)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(corrupt(at, format!("invalid {}: {}", what, other))),
+        }
+    }
+
+    fn string(&mut self, what: &str) -> Result<String, StorageError> {
+        let len = self.u16(what)? as usize;
+        let at = Location::Offset(self.offset);
+        String::from_utf8(self.bytes(len, what)?)
+            .map_err(|_| corrupt(at, format!("{} is not valid UTF-8", what)))
+    }
+
+    fn state(&mut self) -> Result<AccountState, StorageError> {
+        let at = Location::Offset(self.offset);
+        match self.u8("state")? {
+            0 => Ok(AccountState::Active),
+            1 => Ok(AccountState::Inactive),
+            2 => Ok(AccountState::Locked),
+            other => Err(corrupt(at, format!("invalid state: {}", other))),
+        }
+    }
+
+    fn time(&mut self, what: &str) -> Result<DateTime<Utc>, StorageError> {
+        let at = Location::Offset(self.offset);
+        let secs = i64::from_le_bytes(self.array(what)?);
+        let nanos = u32::from_le_bytes(self.array(what)?);
+        DateTime::from_timestamp(secs, nanos)
+            .ok_or_else(|| corrupt(at, format!("{} out of range", what)))
+    }
+
+    fn record_v1(&mut self) -> Result<RecordV1, StorageError> {
+        Ok(RecordV1 {
+            id: self.u64("id")?,
+            name: self.string("name")?,
+            email: self.string("email")?,
+            active: self.bool("active flag")?,
+            sign_in_count: self.u64("sign-in count")?,
+        })
+    }
+
+    fn record_v2(&mut self) -> Result<RecordV2, StorageError> {
+        Ok(RecordV2 {
+            id: self.u64("id")?,
+            username: self.string("username")?,
+            email: self.string("email")?,
+            state: self.state()?,
+            sign_in_count: self.u64("sign-in count")?,
+            created_at: self.time("creation time")?,
+            last_sign_in: self.optional_time("last sign-in time")?,
+        })
+    }
+
+    fn record_v3(&mut self) -> Result<RecordV3, StorageError> {
+        Ok(RecordV3 {
+            id: self.u64("id")?,
+            username: self.string("username")?,
+            email: self.string("email")?,
+            state: self.state()?,
+            sign_in_count: self.u64("sign-in count")?,
+            created_at: self.time("creation time")?,
+            last_sign_in: self.optional_time("last sign-in time")?,
+            password: if self.bool("password flag")? {
+                Some(self.string("password hash")?)
+            } else {
+                None
+            },
+            failed_sign_ins: self.u32("failed sign-in count")?,
+            locked_until: self.optional_time("lock expiry time")?,
+        })
+    }
+
+    fn optional_time(&mut self, what: &str) -> Result<Option<DateTime<Utc>>, StorageError> {
+        if self.bool(what)? {
+            Ok(Some(self.time(what)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn read_binary<R: BufRead>(reader: R) -> Result<Vec<(UserId, User)>, StorageError> {
+    let mut reader = BinaryReader {
+        inner: reader,
+        offset: 0,
+    };
+    if &reader.array::<4>("header")? != MAGIC {
+        return Err(corrupt(Location::Offset(0), "not a users file"));
+    }
+    let version = check_version(reader.u16("header")?)?;
+
+    let mut loaded = Loaded::default();
+    while !reader.at_end()? {
+        let at = Location::Offset(reader.offset);
+        let record = match version {
+            1 => RecordV2::from(reader.record_v1()?).into(),
+            2 => reader.record_v2()?.into(),
+            _ => reader.record_v3()?,
+        };
+        loaded.push(record, at)?;
+    }
+    Ok(loaded.users)
+}
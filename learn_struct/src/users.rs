@@ -0,0 +1,427 @@
+// 用户账号：在main里那个只有name/email/active/sign_in_count的User基础上，
+// 用新类型（newtype）保证邮箱和用户名一定是合法的，用枚举代替active表示账号状态，
+// 并且记录创建时间和最近一次登录的时间
+use crate::auth::PasswordHash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 元组结构体包一层String就是一个新类型，只能通过parse构造，
+// 所以拿到Email时不需要再检查格式
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Email(String);
+
+impl Email {
+    // 只做基本的检查：恰好一个@，本地部分不超过64个字符，域名至少有两段，
+    // 每段只包含字母、数字和连字符；域名不区分大小写，统一转成小写
+    pub fn parse(input: &str) -> Result<Email, UserError> {
+        let input = input.trim();
+        let invalid = |reason| UserError::InvalidEmail {
+            input: input.to_string(),
+            reason,
+        };
+
+        let (local, domain) = match input.split_once('@') {
+            Some(parts) => parts,
+            None => return Err(invalid("missing '@'")),
+        };
+        if local.is_empty() || local.len() > 64 {
+            return Err(invalid("the part before '@' must be 1 to 64 characters"));
+        }
+        if domain.contains('@') {
+            return Err(invalid("more than one '@'"));
+        }
+        if local.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(invalid("contains whitespace"));
+        }
+        let labels: Vec<&str> = domain.split('.').collect();
+        if labels.len() < 2 {
+            return Err(invalid("the domain needs a dot, like example.com"));
+        }
+        let valid_label = |label: &&str| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        if !labels.iter().all(valid_label) {
+            return Err(invalid(
+                "the domain may only contain letters, digits, '-' and '.'",
+            ));
+        }
+
+        Ok(Email(format!("{}@{}", local, domain.to_ascii_lowercase())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// 用户名：3到32个字符，字母开头，只能包含ASCII字母、数字和 _ - .
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Username(String);
+
+impl Username {
+    pub const MIN_LEN: usize = 3;
+    pub const MAX_LEN: usize = 32;
+
+    pub fn parse(input: &str) -> Result<Username, UserError> {
+        let input = input.trim();
+        let invalid = |reason| UserError::InvalidUsername {
+            input: input.to_string(),
+            reason,
+        };
+
+        if input.len() < Username::MIN_LEN || input.len() > Username::MAX_LEN {
+            return Err(invalid("must be 3 to 32 characters"));
+        }
+        if !input.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(invalid("must start with a letter"));
+        }
+        if !input
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        {
+            return Err(invalid(
+                "may only contain letters, digits, '_', '-' and '.'",
+            ));
+        }
+        Ok(Username(input.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// 账号状态，代替原来的active: bool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountState {
+    Active,
+    // 用户或者管理员停用的账号
+    Inactive,
+    // 因为安全原因锁定的账号，必须先解锁才能做其他操作
+    Locked,
+}
+
+impl fmt::Display for AccountState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AccountState::Active => "active",
+            AccountState::Inactive => "inactive",
+            AccountState::Locked => "locked",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// 状态转换的动作，用于错误信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Activate,
+    Deactivate,
+    Lock,
+    Unlock,
+    SignIn,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Transition::Activate => "activate",
+            Transition::Deactivate => "deactivate",
+            Transition::Lock => "lock",
+            Transition::Unlock => "unlock",
+            Transition::SignIn => "sign in to",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserError {
+    InvalidEmail {
+        input: String,
+        reason: &'static str,
+    },
+    InvalidUsername {
+        input: String,
+        reason: &'static str,
+    },
+    // 构造时缺少必填的字段
+    MissingField(&'static str),
+    // 保存的密码哈希格式不对
+    InvalidPasswordHash(&'static str),
+    // 当前状态下不允许这个转换，比如锁定的账号不能直接停用
+    IllegalTransition {
+        from: AccountState,
+        transition: Transition,
+    },
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UserError::InvalidEmail { input, reason } => {
+                write!(f, "invalid email '{}': {}", input, reason)
+            }
+            UserError::InvalidUsername { input, reason } => {
+                write!(f, "invalid username '{}': {}", input, reason)
+            }
+            UserError::MissingField(field) => write!(f, "missing required field: {}", field),
+            UserError::InvalidPasswordHash(reason) => {
+                write!(f, "invalid password hash: {}", reason)
+            }
+            UserError::IllegalTransition { from, transition } => {
+                write!(f, "cannot {} an account that is {}", transition, from)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+// 字段都是私有的，只能通过UserBuilder创建，通过方法修改，保证状态转换都经过检查
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    username: Username,
+    email: Email,
+    state: AccountState,
+    sign_in_count: u64,
+    created_at: DateTime<Utc>,
+    last_sign_in: Option<DateTime<Utc>>,
+    // 没有设置密码的账号不能用密码登录
+    password: Option<PasswordHash>,
+    // 上次登录成功之后连续输错密码的次数
+    failed_sign_ins: u32,
+    // 因为输错密码被锁定时，自动解锁的时间
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl User {
+    pub fn builder() -> UserBuilder {
+        UserBuilder::default()
+    }
+
+    pub fn username(&self) -> &Username {
+        &self.username
+    }
+
+    pub fn email(&self) -> &Email {
+        &self.email
+    }
+
+    pub fn state(&self) -> AccountState {
+        self.state
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state == AccountState::Active
+    }
+
+    pub fn sign_in_count(&self) -> u64 {
+        self.sign_in_count
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn last_sign_in(&self) -> Option<DateTime<Utc>> {
+        self.last_sign_in
+    }
+
+    pub fn password(&self) -> Option<&PasswordHash> {
+        self.password.as_ref()
+    }
+
+    pub fn failed_sign_ins(&self) -> u32 {
+        self.failed_sign_ins
+    }
+
+    pub fn locked_until(&self) -> Option<DateTime<Utc>> {
+        self.locked_until
+    }
+
+    pub fn set_email(&mut self, email: Email) {
+        self.email = email;
+    }
+
+    pub fn set_password(&mut self, password: PasswordHash) {
+        self.password = Some(password);
+    }
+
+    // 记录一次成功的登录，只有正常状态的账号可以登录
+    pub fn record_sign_in(&mut self, at: DateTime<Utc>) -> Result<(), UserError> {
+        if self.state != AccountState::Active {
+            return Err(UserError::IllegalTransition {
+                from: self.state,
+                transition: Transition::SignIn,
+            });
+        }
+        self.sign_in_count += 1;
+        self.last_sign_in = Some(at);
+        self.failed_sign_ins = 0;
+        Ok(())
+    }
+
+    // 记录一次输错密码，返回连续输错的次数，是否锁定由调用方决定
+    pub fn record_failed_sign_in(&mut self) -> u32 {
+        self.failed_sign_ins = self.failed_sign_ins.saturating_add(1);
+        self.failed_sign_ins
+    }
+
+    // 停用的账号可以重新启用，锁定的账号要用unlock
+    pub fn activate(&mut self) -> Result<(), UserError> {
+        self.transition(
+            Transition::Activate,
+            AccountState::Inactive,
+            AccountState::Active,
+        )
+    }
+
+    pub fn deactivate(&mut self) -> Result<(), UserError> {
+        self.transition(
+            Transition::Deactivate,
+            AccountState::Active,
+            AccountState::Inactive,
+        )
+    }
+
+    // 只锁定正常的账号，停用的账号本来就不能登录
+    pub fn lock(&mut self) -> Result<(), UserError> {
+        self.lock_until(None)
+    }
+
+    // 锁定到指定的时间，到时间后登录时自动解锁，None表示只能手动解锁
+    pub fn lock_until(&mut self, until: Option<DateTime<Utc>>) -> Result<(), UserError> {
+        self.transition(Transition::Lock, AccountState::Active, AccountState::Locked)?;
+        self.locked_until = until;
+        Ok(())
+    }
+
+    // 解锁同时清空输错密码的次数
+    pub fn unlock(&mut self) -> Result<(), UserError> {
+        self.transition(
+            Transition::Unlock,
+            AccountState::Locked,
+            AccountState::Active,
+        )?;
+        self.failed_sign_ins = 0;
+        self.locked_until = None;
+        Ok(())
+    }
+
+    fn transition(
+        &mut self,
+        transition: Transition,
+        from: AccountState,
+        to: AccountState,
+    ) -> Result<(), UserError> {
+        if self.state != from {
+            return Err(UserError::IllegalTransition {
+                from: self.state,
+                transition,
+            });
+        }
+        self.state = to;
+        Ok(())
+    }
+}
+
+// 构造User的builder，用户名和邮箱是必填的，其他字段有默认值
+// 字符串在build时才校验，这样可以一次链式调用写完，最后统一处理错误
+#[derive(Debug, Clone, Default)]
+pub struct UserBuilder {
+    username: Option<String>,
+    email: Option<String>,
+    state: Option<AccountState>,
+    sign_in_count: u64,
+    created_at: Option<DateTime<Utc>>,
+    last_sign_in: Option<DateTime<Utc>>,
+    password: Option<PasswordHash>,
+    failed_sign_ins: u32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl UserBuilder {
+    pub fn username<S: Into<String>>(mut self, username: S) -> UserBuilder {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn email<S: Into<String>>(mut self, email: S) -> UserBuilder {
+        self.email = Some(email.into());
+        self
+    }
+
+    // 默认创建正常状态的账号
+    pub fn state(mut self, state: AccountState) -> UserBuilder {
+        self.state = Some(state);
+        self
+    }
+
+    // 默认是build的时间
+    pub fn created_at(mut self, at: DateTime<Utc>) -> UserBuilder {
+        self.created_at = Some(at);
+        self
+    }
+
+    // 登录次数和最近登录时间默认是新用户的0和None，从文件恢复用户时才需要设置
+    pub fn sign_in_count(mut self, count: u64) -> UserBuilder {
+        self.sign_in_count = count;
+        self
+    }
+
+    pub fn last_sign_in(mut self, at: Option<DateTime<Utc>>) -> UserBuilder {
+        self.last_sign_in = at;
+        self
+    }
+
+    pub fn password(mut self, password: PasswordHash) -> UserBuilder {
+        self.password = Some(password);
+        self
+    }
+
+    pub fn failed_sign_ins(mut self, count: u32) -> UserBuilder {
+        self.failed_sign_ins = count;
+        self
+    }
+
+    // 只有锁定的账号才有自动解锁的时间，其他状态下忽略
+    pub fn locked_until(mut self, until: Option<DateTime<Utc>>) -> UserBuilder {
+        self.locked_until = until;
+        self
+    }
+
+    pub fn build(self) -> Result<User, UserError> {
+        let username = self.username.ok_or(UserError::MissingField("username"))?;
+        let email = self.email.ok_or(UserError::MissingField("email"))?;
+        let state = self.state.unwrap_or(AccountState::Active);
+        Ok(User {
+            username: Username::parse(&username)?,
+            email: Email::parse(&email)?,
+            state,
+            sign_in_count: self.sign_in_count,
+            created_at: self.created_at.unwrap_or_else(Utc::now),
+            last_sign_in: self.last_sign_in,
+            password: self.password,
+            failed_sign_ins: self.failed_sign_ins,
+            locked_until: self.locked_until.filter(|_| state == AccountState::Locked),
+        })
+    }
+}
@@ -0,0 +1,217 @@
+// 密码哈希和登录：时间由FakeClock控制，可以直接跳过锁定的时间
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use learn_struct::auth::{Authenticator, LockoutPolicy, PasswordHash, SignInResult};
+use learn_struct::clock::FakeClock;
+use learn_struct::repository::{InMemoryUserRepository, UserId, UserRepository};
+use learn_struct::users::{AccountState, User};
+use std::sync::Arc;
+use std::thread;
+
+// 测试时用较少的迭代次数，不然debug版本每次计算哈希都要等很久
+const ITERATIONS: u32 = 1000;
+const PASSWORD: &str = "correct horse";
+
+struct Fixture {
+    start: DateTime<Utc>,
+    clock: Arc<FakeClock>,
+    repo: Arc<InMemoryUserRepository>,
+    auth: Authenticator,
+    id: UserId,
+}
+
+impl Fixture {
+    // 创建一个已经设置了密码的用户
+    fn new(policy: LockoutPolicy) -> Fixture {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+        let clock = Arc::new(FakeClock::new(start));
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let auth = Authenticator::new(repo.clone(), clock.clone())
+            .with_iterations(ITERATIONS)
+            .with_policy(policy);
+        let id = repo.create(new_user()).unwrap();
+        auth.set_password(id, PASSWORD).unwrap();
+        Fixture {
+            start,
+            clock,
+            repo,
+            auth,
+            id,
+        }
+    }
+
+    fn sign_in(&self, password: &str) -> SignInResult {
+        self.auth.sign_in("xiaoguo", password)
+    }
+
+    fn user(&self) -> User {
+        self.repo.get(self.id).unwrap()
+    }
+}
+
+fn new_user() -> User {
+    User::builder()
+        .username("xiaoguo")
+        .email("dot@dot.com")
+        .build()
+        .unwrap()
+}
+
+fn three_strikes() -> LockoutPolicy {
+    LockoutPolicy {
+        max_failed_attempts: 3,
+        lockout: Some(Duration::minutes(10)),
+    }
+}
+
+#[test]
+fn password_hash_round_trip() {
+    let stored = PasswordHash::with_iterations(PASSWORD, ITERATIONS);
+    assert!(stored.verify(PASSWORD));
+    assert!(!stored.verify("Correct horse"));
+    // 每次使用不同的盐
+    assert_ne!(stored, PasswordHash::with_iterations(PASSWORD, ITERATIONS));
+    assert_eq!(PasswordHash::parse(&stored.to_string()).unwrap(), stored);
+
+    assert!(PasswordHash::parse("md5$abc").is_err());
+    // 迭代次数为0或者过大的哈希读取时就拒绝，不会在登录时卡住
+    for &iterations in ["0", "10000001", "4294967295"].iter() {
+        let text = stored
+            .to_string()
+            .replacen("$1000$", &format!("${}$", iterations), 1);
+        assert!(PasswordHash::parse(&text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn set_password_uses_authenticator_iterations() {
+    let f = Fixture::new(LockoutPolicy::default());
+    assert_eq!(f.user().password().unwrap().iterations(), ITERATIONS);
+}
+
+#[test]
+fn sign_in_by_username_or_email() {
+    let f = Fixture::new(three_strikes());
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Ok(f.id));
+    assert_eq!(
+        f.auth.sign_in("DOT@dot.com", PASSWORD),
+        SignInResult::Ok(f.id)
+    );
+    // 用户不存在和密码错误返回同样的结果
+    assert_eq!(
+        f.auth.sign_in("nobody", PASSWORD),
+        SignInResult::WrongPassword
+    );
+    assert_eq!(f.user().sign_in_count(), 2);
+    assert_eq!(f.user().last_sign_in(), Some(f.start));
+}
+
+#[test]
+fn user_without_password_cannot_sign_in() {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    let repo = Arc::new(InMemoryUserRepository::new());
+    let auth = Authenticator::new(repo.clone(), Arc::new(FakeClock::new(start)))
+        .with_iterations(ITERATIONS)
+        .with_policy(three_strikes());
+    let id = repo.create(new_user()).unwrap();
+
+    // 没有设置密码，不会记为输错，也就不会被锁定
+    for _ in 0..5 {
+        assert_eq!(auth.sign_in("xiaoguo", ""), SignInResult::WrongPassword);
+    }
+    let user = repo.get(id).unwrap();
+    assert_eq!(
+        (user.state(), user.failed_sign_ins()),
+        (AccountState::Active, 0)
+    );
+}
+
+#[test]
+fn lockout_expires() {
+    let f = Fixture::new(three_strikes());
+    f.clock.advance(Duration::minutes(1));
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    let until = f.start + Duration::minutes(11);
+    assert_eq!(
+        f.sign_in("wrong"),
+        SignInResult::Locked { until: Some(until) }
+    );
+    // 输错不增加登录次数
+    assert_eq!(f.user().sign_in_count(), 0);
+    assert_eq!(f.user().failed_sign_ins(), 3);
+
+    // 锁定期间密码正确也不能登录，到时间后自动解锁
+    f.clock.advance(Duration::minutes(9));
+    assert_eq!(
+        f.sign_in(PASSWORD),
+        SignInResult::Locked { until: Some(until) }
+    );
+    f.clock.advance(Duration::minutes(1));
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Ok(f.id));
+    let user = f.user();
+    assert_eq!(
+        (user.state(), user.failed_sign_ins(), user.sign_in_count()),
+        (AccountState::Active, 0, 1)
+    );
+    assert_eq!(user.last_sign_in(), Some(until));
+}
+
+#[test]
+fn successful_sign_in_resets_failures() {
+    let f = Fixture::new(three_strikes());
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Ok(f.id));
+    assert_eq!(f.user().failed_sign_ins(), 0);
+    // 重新计数，再输错两次还不会锁定
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    assert_eq!(f.user().state(), AccountState::Active);
+}
+
+#[test]
+fn inactive_account_is_not_locked() {
+    let f = Fixture::new(three_strikes());
+    f.repo
+        .modify(f.id, &mut |user| user.deactivate().unwrap())
+        .unwrap();
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Inactive);
+    for _ in 0..5 {
+        assert_eq!(f.sign_in("wrong"), SignInResult::WrongPassword);
+    }
+    assert_eq!(f.user().state(), AccountState::Inactive);
+}
+
+#[test]
+fn lockout_without_expiry_needs_unlock() {
+    let f = Fixture::new(LockoutPolicy {
+        max_failed_attempts: 1,
+        lockout: None,
+    });
+    assert_eq!(f.sign_in("wrong"), SignInResult::Locked { until: None });
+    f.clock.advance(Duration::days(365));
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Locked { until: None });
+    f.repo
+        .modify(f.id, &mut |user| user.unlock().unwrap())
+        .unwrap();
+    assert_eq!(f.sign_in(PASSWORD), SignInResult::Ok(f.id));
+}
+
+#[test]
+fn concurrent_failures_are_all_recorded() {
+    let f = Fixture::new(LockoutPolicy {
+        max_failed_attempts: 100,
+        lockout: None,
+    });
+    let auth = Arc::new(f.auth);
+    let handles: Vec<_> = (0..20)
+        .map(|_| {
+            let auth = Arc::clone(&auth);
+            thread::spawn(move || auth.sign_in("xiaoguo", "wrong"))
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), SignInResult::WrongPassword);
+    }
+    assert_eq!(f.repo.get(f.id).unwrap().failed_sign_ins(), 20);
+}
@@ -0,0 +1,253 @@
+// 用户仓库：增删改查、不区分大小写的唯一性约束（包括多个线程同时写入），以及过滤和分页
+use chrono::{TimeZone, Utc};
+use learn_struct::repository::{
+    InMemoryUserRepository, Page, RepositoryError, UserFilter, UserId, UserRepository,
+};
+use learn_struct::users::{Email, User};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+const THREADS: usize = 16;
+
+fn new_user(username: &str, email: &str) -> User {
+    User::builder()
+        .username(username)
+        .email(email)
+        .build()
+        .unwrap()
+}
+
+// 所有线程在Barrier处一起出发，尽量让它们同时抢同一个写锁
+fn race<T, F>(repo: &Arc<dyn UserRepository>, f: F) -> Vec<T>
+where
+    T: Send + 'static,
+    F: Fn(&dyn UserRepository, usize) -> T + Send + Sync + 'static,
+{
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let f = Arc::new(f);
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let repo = Arc::clone(repo);
+            let barrier = Arc::clone(&barrier);
+            let f = Arc::clone(&f);
+            thread::spawn(move || {
+                barrier.wait();
+                f(repo.as_ref(), i)
+            })
+        })
+        .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+// 大小写轮流变化
+fn mixed_case(text: &str, i: usize) -> String {
+    text.chars()
+        .enumerate()
+        .map(|(j, c)| {
+            if (i >> (j % 4)) & 1 == 1 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn create_update_and_delete() {
+    let repo = InMemoryUserRepository::new();
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+    assert_eq!(repo.get(id).unwrap().username().as_str(), "xiaoguo");
+    assert_eq!(repo.find_by_username("XIAOGUO").unwrap().0, id);
+    assert_eq!(repo.find_by_email("Dot@Dot.com").unwrap().0, id);
+
+    // 改成别人的邮箱不行，改成自己原来的邮箱可以
+    let other = repo.create(new_user("other", "other@dot.com")).unwrap();
+    let mut user = repo.get(id).unwrap();
+    user.set_email(Email::parse("OTHER@dot.com").unwrap());
+    assert_eq!(
+        repo.update(id, user.clone()),
+        Err(RepositoryError::DuplicateEmail {
+            email: String::from("OTHER@dot.com"),
+            owner: other,
+        })
+    );
+    user.set_email(Email::parse("Dot@dot.com").unwrap());
+    repo.update(id, user).unwrap();
+    assert_eq!(repo.get(id).unwrap().email().as_str(), "Dot@dot.com");
+
+    assert_eq!(repo.delete(other).unwrap().username().as_str(), "other");
+    assert_eq!(repo.delete(other), Err(RepositoryError::NotFound(other)));
+    assert_eq!(repo.get(other), None);
+    assert_eq!(
+        repo.update(other, new_user("other", "other@dot.com")),
+        Err(RepositoryError::NotFound(other))
+    );
+    // 删除之后邮箱和用户名可以重新注册，id不会重复使用
+    let again = repo.create(new_user("Other", "other@dot.com")).unwrap();
+    assert!(again > other);
+    assert_eq!(repo.len(), 2);
+}
+
+#[test]
+fn duplicates_ignore_case() {
+    let repo = InMemoryUserRepository::new();
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+    assert_eq!(
+        repo.create(new_user("other", "DOT@dot.com")),
+        Err(RepositoryError::DuplicateEmail {
+            email: String::from("DOT@dot.com"),
+            owner: id,
+        })
+    );
+    assert_eq!(
+        repo.create(new_user("XiaoGuo", "other@dot.com")),
+        Err(RepositoryError::DuplicateUsername {
+            username: String::from("XiaoGuo"),
+            owner: id,
+        })
+    );
+    assert_eq!(repo.len(), 1);
+}
+
+#[test]
+fn concurrent_creates_with_same_email() {
+    let repo: Arc<dyn UserRepository> = Arc::new(InMemoryUserRepository::new());
+    let results = race(&repo, |repo, i| {
+        let email = mixed_case("race@dot.com", i);
+        repo.create(new_user(&format!("racer{}", i), &email))
+    });
+    let created: Vec<UserId> = results.iter().filter_map(|r| r.clone().ok()).collect();
+    assert_eq!(created.len(), 1);
+    assert!(results.iter().all(|r| match r {
+        Ok(_) => true,
+        Err(RepositoryError::DuplicateEmail { owner, .. }) => *owner == created[0],
+        Err(_) => false,
+    }));
+    assert_eq!(repo.len(), 1);
+}
+
+#[test]
+fn concurrent_creates_with_same_username() {
+    let repo: Arc<dyn UserRepository> = Arc::new(InMemoryUserRepository::new());
+    let results = race(&repo, |repo, i| {
+        let username = mixed_case("racer", i);
+        repo.create(new_user(&username, &format!("racer{}@dot.com", i)))
+    });
+    let created: Vec<UserId> = results.iter().filter_map(|r| r.clone().ok()).collect();
+    assert_eq!(created.len(), 1);
+    assert!(results.iter().all(|r| match r {
+        Ok(_) => true,
+        Err(RepositoryError::DuplicateUsername { owner, .. }) => *owner == created[0],
+        Err(_) => false,
+    }));
+    assert_eq!(repo.len(), 1);
+}
+
+#[test]
+fn concurrent_renames_to_same_email_and_username() {
+    let repo: Arc<dyn UserRepository> = Arc::new(InMemoryUserRepository::new());
+    let ids: Vec<UserId> = (0..THREADS)
+        .map(|i| {
+            repo.create(new_user(
+                &format!("user{}", i),
+                &format!("user{}@dot.com", i),
+            ))
+            .unwrap()
+        })
+        .collect();
+
+    // 一半线程改邮箱，一半改用户名，各自只有一个成功
+    let ids = Arc::new(ids);
+    let shared = Arc::clone(&ids);
+    let results = race(&repo, move |repo, i| {
+        if i % 2 == 0 {
+            repo.modify(shared[i], &mut |user| {
+                user.set_email(Email::parse(&mixed_case("taken@dot.com", i)).unwrap())
+            })
+            .map(|_| ())
+        } else {
+            let email = format!("user{}@dot.com", i);
+            repo.update(shared[i], new_user(&mixed_case("taken", i), &email))
+        }
+    });
+    let emails = results.iter().step_by(2);
+    let usernames = results.iter().skip(1).step_by(2);
+    assert_eq!(emails.clone().filter(|r| r.is_ok()).count(), 1);
+    assert_eq!(usernames.clone().filter(|r| r.is_ok()).count(), 1);
+    assert!(emails
+        .filter_map(|r| r.as_ref().err())
+        .all(|e| matches!(e, RepositoryError::DuplicateEmail { .. })));
+    assert!(usernames
+        .filter_map(|r| r.as_ref().err())
+        .all(|e| matches!(e, RepositoryError::DuplicateUsername { .. })));
+
+    // 失败的修改没有被保存
+    let taken_email = repo.find_by_email("TAKEN@dot.com").unwrap().0;
+    let taken_username = repo.find_by_username("TAKEN").unwrap().0;
+    for (i, &id) in ids.iter().enumerate() {
+        let user = repo.get(id).unwrap();
+        if id != taken_email {
+            assert_eq!(user.email().to_string(), format!("user{}@dot.com", i));
+        }
+        if id != taken_username {
+            assert_eq!(user.username().to_string(), format!("user{}", i));
+        }
+    }
+}
+
+#[test]
+fn filter_and_paginate() {
+    let repo = InMemoryUserRepository::new();
+    let now = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    for i in 0..22 {
+        let mut user = new_user(&format!("user{}", i), &format!("user{}@dot.com", i));
+        for _ in 0..i % 4 {
+            user.record_sign_in(now).unwrap();
+        }
+        if i % 5 == 0 {
+            user.deactivate().unwrap();
+        }
+        repo.create(user).unwrap();
+    }
+
+    let filter = UserFilter::default().active(true).min_sign_ins(2);
+    let all = repo.list(&filter, Page::all());
+    assert!(all
+        .items
+        .iter()
+        .all(|(_, user)| user.is_active() && user.sign_in_count() >= 2));
+    let expected = (0..22)
+        .filter(|i| i % 5 != 0 && i % 4 >= 2)
+        .map(|i| UserId(i + 1))
+        .collect::<Vec<_>>();
+    let ids = |items: &[(UserId, User)]| items.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    assert_eq!(ids(&all.items), expected);
+    assert_eq!(all.total, expected.len());
+
+    // 按页拼起来和一次取全部一样，最后一页不满
+    let mut pages = Vec::new();
+    for number in 0.. {
+        let page = repo.list(&filter, Page::new(number, 4));
+        assert_eq!(page.total, all.total);
+        if page.items.is_empty() {
+            break;
+        }
+        assert!(page.items.len() <= 4);
+        pages.extend(page.items);
+    }
+    assert_eq!(pages, all.items);
+
+    assert!(repo.list(&filter, Page::new(100, 4)).items.is_empty());
+    assert!(repo
+        .list(&filter, Page::new(usize::MAX, usize::MAX))
+        .items
+        .is_empty());
+    assert!(repo.list(&filter, Page::new(0, 0)).items.is_empty());
+    assert_eq!(
+        repo.list(&UserFilter::default().active(false), Page::all())
+            .total,
+        5
+    );
+    assert_eq!(repo.list(&UserFilter::default(), Page::all()).total, 22);
+}
@@ -0,0 +1,232 @@
+// 会话：令牌的验证、两种过期、撤销、后台清理，以及跟着账号状态失效
+// 过期全部由FakeClock驱动，不依赖真实的时间
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use learn_struct::clock::FakeClock;
+use learn_struct::repository::{InMemoryUserRepository, UserId, UserRepository};
+use learn_struct::sessions::{Cleanup, SessionError, SessionPolicy, SessionStore};
+use learn_struct::users::User;
+use std::sync::mpsc;
+use std::sync::Arc;
+
+struct Fixture {
+    start: DateTime<Utc>,
+    clock: Arc<FakeClock>,
+    repo: Arc<InMemoryUserRepository>,
+    sessions: Arc<SessionStore>,
+    id: UserId,
+    other: UserId,
+}
+
+// 绝对过期8小时，空闲过期30分钟
+fn setup() -> Fixture {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap();
+    let clock = Arc::new(FakeClock::new(start));
+    let repo = Arc::new(InMemoryUserRepository::new());
+    let sessions = Arc::new(SessionStore::new(repo.clone(), clock.clone()).with_policy(
+        SessionPolicy {
+            absolute: Duration::hours(8),
+            idle: Duration::minutes(30),
+        },
+    ));
+    let id = repo.create(new_user("xiaoguo", "dot@dot.com")).unwrap();
+    let other = repo.create(new_user("other", "other@dot.com")).unwrap();
+    Fixture {
+        start,
+        clock,
+        repo,
+        sessions,
+        id,
+        other,
+    }
+}
+
+fn new_user(username: &str, email: &str) -> User {
+    User::builder()
+        .username(username)
+        .email(email)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn tokens_identify_their_user() {
+    let f = setup();
+    let laptop = f.sessions.create(f.id).unwrap();
+    let phone = f.sessions.create(f.id).unwrap();
+    assert_ne!(laptop, phone);
+    assert_eq!(laptop.as_str().len(), 64);
+
+    let session = f.sessions.validate(laptop.as_str()).unwrap();
+    assert_eq!(session.user_id, f.id);
+    assert_eq!(session.created_at, f.start);
+    assert_eq!(
+        f.sessions.validate("not a token"),
+        Err(SessionError::Unknown)
+    );
+    assert_eq!(f.sessions.list(f.id).len(), 2);
+    assert!(f.sessions.list(f.other).is_empty());
+}
+
+#[test]
+fn revoke_by_token_id_or_user() {
+    let f = setup();
+    let laptop = f.sessions.create(f.id).unwrap();
+    let phone = f.sessions.create(f.id).unwrap();
+
+    // 按编号退出手机上的登录，别的用户不能撤销
+    let phone_id = f.sessions.validate(phone.as_str()).unwrap().id;
+    assert!(!f.sessions.revoke_id(f.other, phone_id));
+    assert!(f.sessions.revoke_id(f.id, phone_id));
+    assert_eq!(
+        f.sessions.validate(phone.as_str()),
+        Err(SessionError::Unknown)
+    );
+
+    let kept = f.sessions.create(f.other).unwrap();
+    assert!(f.sessions.revoke(laptop.as_str()));
+    assert!(!f.sessions.revoke(laptop.as_str()));
+
+    // 修改密码后退出所有设备，别的用户不受影响
+    for _ in 0..3 {
+        f.sessions.create(f.id).unwrap();
+    }
+    assert_eq!(f.sessions.revoke_all(f.id), 3);
+    assert_eq!(f.sessions.len(), 1);
+    assert!(f.sessions.validate(kept.as_str()).is_ok());
+}
+
+#[test]
+fn active_session_expires_after_absolute_limit() {
+    let f = setup();
+    let token = f.sessions.create(f.id).unwrap();
+    // 一直在用的会话不会空闲过期，但是8小时后绝对过期
+    for _ in 0..23 {
+        f.clock.advance(Duration::minutes(20));
+        f.sessions.validate(token.as_str()).unwrap();
+    }
+    f.clock.advance(Duration::minutes(20));
+    assert_eq!(
+        f.sessions.validate(token.as_str()),
+        Err(SessionError::Expired {
+            at: f.start + Duration::hours(8)
+        })
+    );
+    // 过期的会话在验证时被删除
+    assert_eq!(
+        f.sessions.validate(token.as_str()),
+        Err(SessionError::Unknown)
+    );
+    assert!(f.sessions.is_empty());
+}
+
+#[test]
+fn unused_session_expires_after_idle_limit() {
+    let f = setup();
+    let token = f.sessions.create(f.id).unwrap();
+    f.clock.advance(Duration::minutes(29));
+    assert!(f.sessions.validate(token.as_str()).is_ok());
+    // 验证后重新计时
+    f.clock.advance(Duration::minutes(29));
+    assert!(f.sessions.validate(token.as_str()).is_ok());
+    f.clock.advance(Duration::minutes(30));
+    assert!(f.sessions.list(f.id).is_empty());
+    assert_eq!(
+        f.sessions.validate(token.as_str()),
+        Err(SessionError::Expired {
+            at: f.start + Duration::minutes(88)
+        })
+    );
+}
+
+#[test]
+fn purge_removes_only_expired_sessions() {
+    let f = setup();
+    for _ in 0..3 {
+        f.sessions.create(f.id).unwrap();
+    }
+    assert_eq!(f.sessions.purge_expired(), 0);
+    f.clock.advance(Duration::hours(1));
+    let fresh = f.sessions.create(f.id).unwrap();
+    assert_eq!(f.sessions.len(), 4);
+    assert_eq!(f.sessions.purge_expired(), 3);
+    assert_eq!(f.sessions.len(), 1);
+    assert!(f.sessions.validate(fresh.as_str()).is_ok());
+}
+
+#[test]
+fn cleanup_thread_purges_expired_sessions() {
+    let f = setup();
+    for _ in 0..3 {
+        f.sessions.create(f.id).unwrap();
+    }
+    f.clock.advance(Duration::hours(1));
+    let fresh = f.sessions.create(f.id).unwrap();
+
+    let (report, purged) = mpsc::channel();
+    let cleanup = Cleanup::spawn_with_report(
+        Arc::clone(&f.sessions),
+        std::time::Duration::from_millis(1),
+        report,
+    );
+    // 等清理线程报告第一遍的结果，超时只是为了让测试失败而不是一直等下去
+    let first = purged
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("cleanup did not run");
+    assert_eq!(first, 3);
+    cleanup.stop();
+    assert_eq!(f.sessions.len(), 1);
+    assert!(f.sessions.validate(fresh.as_str()).is_ok());
+}
+
+#[test]
+fn deactivated_user_loses_all_sessions() {
+    let f = setup();
+    let desktop = f.sessions.create(f.id).unwrap();
+    let laptop = f.sessions.create(f.id).unwrap();
+    let other = f.sessions.create(f.other).unwrap();
+    f.repo
+        .modify(f.id, &mut |user| user.deactivate().unwrap())
+        .unwrap();
+
+    // 停用账号后已经发出的令牌马上失效，这个用户的会话全部删除
+    assert_eq!(
+        f.sessions.validate(desktop.as_str()),
+        Err(SessionError::Inactive(f.id))
+    );
+    assert!(f.sessions.list(f.id).is_empty());
+    assert_eq!(
+        f.sessions.validate(laptop.as_str()),
+        Err(SessionError::Unknown)
+    );
+    // 也不能再创建新的会话，别的用户不受影响
+    assert_eq!(f.sessions.create(f.id), Err(SessionError::Inactive(f.id)));
+    assert!(f.sessions.validate(other.as_str()).is_ok());
+
+    // 重新激活后可以正常登录
+    f.repo
+        .modify(f.id, &mut |user| user.activate().unwrap())
+        .unwrap();
+    let token = f.sessions.create(f.id).unwrap();
+    assert!(f.sessions.validate(token.as_str()).is_ok());
+}
+
+#[test]
+fn locked_or_deleted_user_loses_sessions() {
+    let f = setup();
+    let token = f.sessions.create(f.other).unwrap();
+    f.repo
+        .modify(f.other, &mut |user| user.lock().unwrap())
+        .unwrap();
+    assert_eq!(
+        f.sessions.validate(token.as_str()),
+        Err(SessionError::Inactive(f.other))
+    );
+
+    let token = f.sessions.create(f.id).unwrap();
+    f.repo.delete(f.id).unwrap();
+    assert_eq!(
+        f.sessions.validate(token.as_str()),
+        Err(SessionError::Inactive(f.id))
+    );
+    assert!(f.sessions.is_empty());
+}
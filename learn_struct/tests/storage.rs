@@ -0,0 +1,409 @@
+// 保存和读取用户：两种格式来回转换后内容不变，版本1到3的文件都能读取，损坏的文件报告出错的位置
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use learn_struct::auth::PasswordHash;
+use learn_struct::repository::{InMemoryUserRepository, UserId, UserRepository};
+use learn_struct::storage::{self, Format, Location, StorageError, CURRENT_VERSION};
+use learn_struct::users::{AccountState, User};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const FORMATS: [Format; 2] = [Format::JsonLines, Format::Binary];
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2020, 1, 1, 8, 0, 0).unwrap()
+}
+
+// 覆盖每个字段的各种取值：有没有密码、三种状态、有没有最近登录和自动解锁时间
+fn sample_users() -> Vec<(UserId, User)> {
+    let active = User::builder()
+        .username("xiaoguo")
+        .email("Dot@dot.com")
+        .created_at(start())
+        .sign_in_count(3)
+        .last_sign_in(Some(start() + Duration::milliseconds(1500)))
+        .password(PasswordHash::with_iterations("correct horse", 1000))
+        .build()
+        .unwrap();
+    let inactive = User::builder()
+        .username("retired")
+        .email("old@dot.com")
+        .state(AccountState::Inactive)
+        .created_at(start())
+        .build()
+        .unwrap();
+    let locked = User::builder()
+        .username("locked")
+        .email("locked@dot.com")
+        .state(AccountState::Locked)
+        .created_at(start())
+        .failed_sign_ins(5)
+        .locked_until(Some(start() + Duration::minutes(15)))
+        .build()
+        .unwrap();
+    let forever = User::builder()
+        .username("forever")
+        .email("forever@dot.com")
+        .state(AccountState::Locked)
+        .created_at(start())
+        .build()
+        .unwrap();
+    vec![
+        (UserId(1), active),
+        (UserId(3), inactive),
+        (UserId(4), locked),
+        (UserId(9), forever),
+    ]
+}
+
+fn save(format: Format, users: &[(UserId, User)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    storage::save(&mut buf, format, users).unwrap();
+    buf
+}
+
+fn load(bytes: &[u8], format: Format) -> Result<Vec<(UserId, User)>, StorageError> {
+    storage::load(bytes, format)
+}
+
+// 按二进制格式拼出文件内容，整数都是小端
+struct Binary(Vec<u8>);
+
+impl Binary {
+    fn header(version: u16) -> Binary {
+        let mut binary = Binary(b"USRS".to_vec());
+        binary.u16(version);
+        binary
+    }
+
+    fn u8(&mut self, value: u8) -> &mut Binary {
+        self.0.push(value);
+        self
+    }
+
+    fn u16(&mut self, value: u16) -> &mut Binary {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Binary {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn str(&mut self, value: &str) -> &mut Binary {
+        self.u16(value.len() as u16);
+        self.0.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    fn time(&mut self, at: DateTime<Utc>) -> &mut Binary {
+        self.0.extend_from_slice(&at.timestamp().to_le_bytes());
+        self.0
+            .extend_from_slice(&at.timestamp_subsec_nanos().to_le_bytes());
+        self
+    }
+
+    fn v1(&mut self, id: u64, name: &str, email: &str, active: u8) -> &mut Binary {
+        self.u64(id).str(name).str(email).u8(active).u64(3)
+    }
+
+    // 状态0是Active，1是Inactive，2是Locked
+    fn v2(&mut self, id: u64, username: &str, email: &str, state: u8) -> &mut Binary {
+        self.u64(id).str(username).str(email).u8(state).u64(2);
+        self.time(start()).u8(1).time(start() + Duration::hours(1))
+    }
+}
+
+#[test]
+fn round_trip_in_both_formats() {
+    let users = sample_users();
+    for &format in FORMATS.iter() {
+        assert_eq!(load(&save(format, &users), format).unwrap(), users);
+        assert_eq!(load(&save(format, &[]), format).unwrap(), vec![]);
+    }
+    let (_, user) = &load(&save(Format::Binary, &users), Format::Binary).unwrap()[0];
+    assert!(user.password().unwrap().verify("correct horse"));
+}
+
+#[test]
+fn saved_files_use_the_current_version() {
+    let users = sample_users();
+    let json = String::from_utf8(save(Format::JsonLines, &users)).unwrap();
+    assert_eq!(
+        json.lines().next().unwrap(),
+        format!(
+            r#"{{"format":"learn_struct.users","version":{}}}"#,
+            CURRENT_VERSION
+        )
+    );
+    assert_eq!(json.lines().count(), users.len() + 1);
+    let binary = save(Format::Binary, &users);
+    assert_eq!(binary[..6], Binary::header(CURRENT_VERSION).0[..]);
+}
+
+#[test]
+fn json_lines_version_1() {
+    let v1 = r#"{"format":"learn_struct.users","version":1}
+{"id":1,"name":"xiaoguo","email":"dot@dot.com","active":true,"sign_in_count":3}
+
+{"id":7,"name":"retired","email":"old@dot.com","active":false,"sign_in_count":0}
+"#;
+    let users = load(v1.as_bytes(), Format::JsonLines).unwrap();
+    assert_eq!(users.len(), 2);
+    let (id, user) = &users[0];
+    assert_eq!(*id, UserId(1));
+    assert_eq!(user.username().as_str(), "xiaoguo");
+    assert_eq!(user.state(), AccountState::Active);
+    assert_eq!(user.sign_in_count(), 3);
+    assert_eq!(user.created_at(), storage::LEGACY_CREATED_AT);
+    assert_eq!(user.last_sign_in(), None);
+    assert_eq!(user.password(), None);
+    assert_eq!(users[1].0, UserId(7));
+    assert_eq!(users[1].1.state(), AccountState::Inactive);
+}
+
+#[test]
+fn binary_version_1() {
+    let mut v1 = Binary::header(1);
+    v1.v1(1, "xiaoguo", "dot@dot.com", 1)
+        .v1(7, "retired", "old@dot.com", 0);
+    let users = load(&v1.0, Format::Binary).unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].1.username().as_str(), "xiaoguo");
+    assert_eq!(users[0].1.sign_in_count(), 3);
+    assert_eq!(users[0].1.created_at(), storage::LEGACY_CREATED_AT);
+    assert_eq!(users[1].0, UserId(7));
+    assert_eq!(users[1].1.state(), AccountState::Inactive);
+}
+
+#[test]
+fn json_lines_version_2() {
+    let v2 = r#"{"format":"learn_struct.users","version":2}
+{"id":2,"username":"xiaoguo","email":"dot@dot.com","state":"locked","sign_in_count":2,"created_at":"2020-01-01T08:00:00Z","last_sign_in":"2020-01-01T09:00:00Z"}
+"#;
+    let users = load(v2.as_bytes(), Format::JsonLines).unwrap();
+    check_version_2(&users);
+}
+
+#[test]
+fn binary_version_2() {
+    let mut v2 = Binary::header(2);
+    v2.v2(2, "xiaoguo", "dot@dot.com", 2);
+    let users = load(&v2.0, Format::Binary).unwrap();
+    check_version_2(&users);
+}
+
+// v2没有密码和输错次数，锁定的账号迁移后需要手动解锁
+fn check_version_2(users: &[(UserId, User)]) {
+    assert_eq!(users.len(), 1);
+    let (id, user) = &users[0];
+    assert_eq!(*id, UserId(2));
+    assert_eq!(user.state(), AccountState::Locked);
+    assert_eq!(user.sign_in_count(), 2);
+    assert_eq!(user.created_at(), start());
+    assert_eq!(user.last_sign_in(), Some(start() + Duration::hours(1)));
+    assert_eq!(user.password(), None);
+    assert_eq!(user.failed_sign_ins(), 0);
+    assert_eq!(user.locked_until(), None);
+}
+
+#[test]
+fn json_lines_version_3() {
+    let hash = PasswordHash::with_iterations("correct horse", 1000);
+    let v3 = format!(
+        r#"{{"format":"learn_struct.users","version":3}}
+{{"id":5,"username":"xiaoguo","email":"dot@dot.com","state":"locked","sign_in_count":1,"created_at":"2020-01-01T08:00:00Z","last_sign_in":null,"password":"{}","failed_sign_ins":4,"locked_until":"2020-01-01T08:15:00Z"}}
+"#,
+        hash
+    );
+    let users = load(v3.as_bytes(), Format::JsonLines).unwrap();
+    let (id, user) = &users[0];
+    assert_eq!(*id, UserId(5));
+    assert_eq!(user.password(), Some(&hash));
+    assert_eq!(user.failed_sign_ins(), 4);
+    assert_eq!(user.locked_until(), Some(start() + Duration::minutes(15)));
+}
+
+#[test]
+fn binary_version_3() {
+    let hash = PasswordHash::with_iterations("correct horse", 1000);
+    let mut v3 = Binary::header(3);
+    v3.u64(5).str("xiaoguo").str("dot@dot.com").u8(2).u64(1);
+    v3.time(start()).u8(0);
+    v3.u8(1).str(&hash.to_string());
+    v3.0.extend_from_slice(&4u32.to_le_bytes());
+    v3.u8(1).time(start() + Duration::minutes(15));
+    let users = load(&v3.0, Format::Binary).unwrap();
+    let (id, user) = &users[0];
+    assert_eq!(*id, UserId(5));
+    assert_eq!(user.password(), Some(&hash));
+    assert_eq!(user.failed_sign_ins(), 4);
+    assert_eq!(user.locked_until(), Some(start() + Duration::minutes(15)));
+}
+
+#[test]
+fn unsupported_versions() {
+    for &version in [0, CURRENT_VERSION + 1].iter() {
+        let json = format!(r#"{{"format":"learn_struct.users","version":{}}}"#, version);
+        assert!(matches!(
+            load(json.as_bytes(), Format::JsonLines),
+            Err(StorageError::UnsupportedVersion(v)) if v == version
+        ));
+        assert!(matches!(
+            load(&Binary::header(version).0, Format::Binary),
+            Err(StorageError::UnsupportedVersion(v)) if v == version
+        ));
+    }
+}
+
+#[test]
+fn json_lines_errors_report_the_line() {
+    let header = r#"{"format":"learn_struct.users","version":2}"#;
+    let good = r#"{"id":1,"username":"xiaoguo","email":"dot@dot.com","state":"active","sign_in_count":0,"created_at":"2020-01-01T00:00:00Z","last_sign_in":null}"#;
+    let at = |text: &str| match load(text.as_bytes(), Format::JsonLines) {
+        Err(StorageError::Corrupt { at, .. }) => at,
+        Err(StorageError::InvalidUser { at, .. }) => at,
+        Err(StorageError::DuplicateId { at, .. }) => at,
+        other => panic!("{:?}", other),
+    };
+
+    assert_eq!(at(""), Location::Line(1));
+    assert_eq!(at(r#"{"format":"other","version":2}"#), Location::Line(1));
+    let unknown_state = good.replace("active", "sleeping");
+    assert_eq!(
+        at(&format!("{}\n{}\n{}\n", header, good, unknown_state)),
+        Location::Line(3)
+    );
+    let truncated = &good[..good.len() - 10];
+    assert_eq!(
+        at(&format!("{}\n{}\n{}\n", header, good, truncated)),
+        Location::Line(3)
+    );
+    let bad_email = good.replace("dot@dot.com", "dot@dot");
+    assert!(matches!(
+        load(
+            format!("{}\n{}\n", header, bad_email).as_bytes(),
+            Format::JsonLines
+        ),
+        Err(StorageError::InvalidUser {
+            at: Location::Line(2),
+            ..
+        })
+    ));
+    assert!(matches!(
+        load(
+            format!("{}\n{}\n\n{}\n", header, good, good).as_bytes(),
+            Format::JsonLines
+        ),
+        Err(StorageError::DuplicateId {
+            at: Location::Line(4),
+            id: UserId(1),
+        })
+    ));
+
+    let mut not_utf8 = format!("{}\n", header).into_bytes();
+    not_utf8.extend_from_slice(b"{\"id\":\xff}\n");
+    assert!(matches!(
+        load(&not_utf8, Format::JsonLines),
+        Err(StorageError::Corrupt {
+            at: Location::Line(2),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn binary_errors_report_the_offset() {
+    let offset = |bytes: &[u8]| match load(bytes, Format::Binary) {
+        Err(StorageError::Corrupt { at, .. }) => at,
+        Err(StorageError::InvalidUser { at, .. }) => at,
+        Err(StorageError::DuplicateId { at, .. }) => at,
+        other => panic!("{:?}", other),
+    };
+
+    assert_eq!(offset(b"PNG\x89 not users"), Location::Offset(0));
+    assert_eq!(offset(b"USR"), Location::Offset(0));
+
+    // 文件头6字节，第一条记录的id、用户名和邮箱之后是是否启用的标记
+    let mut v1 = Binary::header(1);
+    v1.v1(1, "xiaoguo", "dot@dot.com", 5);
+    assert_eq!(offset(&v1.0), Location::Offset(6 + 8 + 2 + 7 + 2 + 11));
+
+    let mut v2 = Binary::header(2);
+    v2.v2(1, "xiaoguo", "dot@dot.com", 9);
+    assert_eq!(offset(&v2.0), Location::Offset(36));
+
+    // 第二条记录的邮箱不合法，位置是这条记录的开头
+    let mut bad_email = Binary::header(2);
+    bad_email.v2(1, "xiaoguo", "dot@dot.com", 0);
+    let second = bad_email.0.len() as u64;
+    bad_email.v2(2, "other", "dot@dot", 0);
+    assert!(matches!(
+        load(&bad_email.0, Format::Binary),
+        Err(StorageError::InvalidUser { at: Location::Offset(at), .. }) if at == second
+    ));
+
+    let mut duplicate = Binary::header(2);
+    duplicate.v2(1, "xiaoguo", "dot@dot.com", 0);
+    let second = duplicate.0.len() as u64;
+    duplicate.v2(1, "other", "other@dot.com", 0);
+    assert!(matches!(
+        load(&duplicate.0, Format::Binary),
+        Err(StorageError::DuplicateId { at: Location::Offset(at), id: UserId(1) }) if at == second
+    ));
+
+    // 最后一个用户没有自动解锁时间，末尾是4字节的输错次数和1字节的标记，
+    // 截掉3个字节后读输错次数时到了文件末尾
+    let saved = save(Format::Binary, &sample_users());
+    let cut = saved.len() - 3;
+    match load(&saved[..cut], Format::Binary) {
+        Err(StorageError::Corrupt { at, reason }) => {
+            assert_eq!(at, Location::Offset(saved.len() as u64 - 5));
+            assert!(reason.contains("failed sign-in count"), "{}", reason);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("learn_struct-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn save_and_load_files() {
+    let users = sample_users();
+    for name in ["users.jsonl", "users.bin"].iter() {
+        let path = temp_path(name);
+        let format = Format::from_path(&path).unwrap();
+        storage::save_file(&path, format, &users).unwrap();
+        let loaded = storage::load_file(&path, format);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), users);
+    }
+    assert_eq!(Format::from_path(&temp_path("users.txt")), None);
+
+    // 从文件恢复仓库，新用户的id接着最大的id分配
+    let repo = InMemoryUserRepository::from_users(users).unwrap();
+    let user = User::builder()
+        .username("new")
+        .email("new@dot.com")
+        .build()
+        .unwrap();
+    assert_eq!(repo.create(user), Ok(UserId(10)));
+}
+
+#[test]
+fn failed_save_removes_the_temp_file() {
+    // 目标位置是一个非空的目录，写完临时文件后改名会失败
+    let path = temp_path("taken.bin");
+    fs::create_dir_all(path.join("inside")).unwrap();
+    let result = storage::save_file(&path, Format::Binary, &sample_users());
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    let tmp_exists = PathBuf::from(tmp).exists();
+    fs::remove_dir_all(&path).unwrap();
+
+    assert!(result.is_err());
+    assert!(!tmp_exists);
+}